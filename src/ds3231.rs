@@ -2,43 +2,101 @@
 
 maybe_async_cfg::content! {
 #![maybe_async_cfg::default(
-    idents(I2cInterface, Ds323x),
+    idents(
+        I2cInterface, ReadData, WriteData, Ds323x,
+        embedded_hal(sync, async = "embedded_hal_async"),
+    ),
 )]
 
 #[maybe_async_cfg::maybe(
-    sync(not(feature = "async")),
+    sync(feature = "sync"),
     async(feature = "async")
 )]
-use crate::{ic,interface::I2cInterface,  BitFlags, Ds323x, CONTROL_POR_VALUE};
+use crate::{
+    interface::{I2cInterface, ReadData, WriteData},
+    Ds323x,
+};
+use crate::{ic, BitFlags, Config, Error, RegisterDump, CONTROL_POR_VALUE};
+use crate::ds323x::DS3231_DUMP_LEN;
 use core::marker::PhantomData;
 
-#[cfg(not(feature = "async"))]
-use embedded_hal::i2c;
-#[cfg(feature = "async")]
-use embedded_hal_async::i2c;
-
 #[maybe_async_cfg::maybe(
-    sync(not(feature = "async")),
+    sync(feature = "sync"),
     async(feature = "async")
 )]
 impl<I2C, E> Ds323x<I2cInterface<I2C>, ic::DS3231>
 where
-    I2C: i2c::I2c<Error = E>,
+    I2C: embedded_hal::i2c::I2c<Error = E>,
 {
     /// Create a new instance of the DS3231 device.
     pub fn new_ds3231(i2c: I2C) -> Self {
+        Ds323x::new_ds3231_with_interface(I2cInterface { i2c })
+    }
+
+    /// Destroy driver instance, return I²C bus instance.
+    pub fn destroy_ds3231(self) -> I2C {
+        self.iface.i2c
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+impl<DI> Ds323x<DI, ic::DS3231> {
+    /// Create a new instance of the DS3231 device with a custom interface.
+    ///
+    /// See the [`interface`](crate::interface) module documentation.
+    pub fn new_ds3231_with_interface(iface: DI) -> Self {
         const STATUS_POR_VALUE: u8 = BitFlags::OSC_STOP | BitFlags::EN32KHZ;
         Ds323x {
-            iface: I2cInterface { i2c },
+            iface,
             control: CONTROL_POR_VALUE,
             status: STATUS_POR_VALUE,
             _ic: PhantomData,
         }
     }
+}
 
-    /// Destroy driver instance, return I²C bus instance.
-    pub fn destroy_ds3231(self) -> I2C {
-        self.iface.i2c
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+impl<DI, E> Ds323x<DI, ic::DS3231>
+where
+    DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+{
+    /// Apply the whole configuration at once.
+    ///
+    /// The control, status and aging offset registers are written in a
+    /// single transaction. The alarm matched flags are not cleared.
+    pub async fn apply_config(&mut self, config: &Config) -> Result<(), Error<E>> {
+        self.write_config(config, 0).await
+    }
+
+    /// Read back the whole configuration at once.
+    ///
+    /// The control, status and aging offset registers are read in a single
+    /// transaction.
+    pub async fn read_config(&mut self) -> Result<Config, Error<E>> {
+        self.read_config_with(|_| ()).await
+    }
+
+    /// Read all the registers at once.
+    ///
+    /// The timekeeping, alarm, control, status, aging offset and temperature
+    /// registers (0x00-0x12) are read in a single transaction.
+    pub async fn dump_registers(&mut self) -> Result<RegisterDump, Error<E>> {
+        self.read_register_dump(DS3231_DUMP_LEN).await
+    }
+
+    /// Write back the configuration from a register dump.
+    ///
+    /// The alarm, control, status and aging offset registers are written in a
+    /// single transaction. The date and time are not restored and neither the
+    /// alarm matched flags nor the oscillator stop flag are modified.
+    pub async fn restore_registers(&mut self, dump: &RegisterDump) -> Result<(), Error<E>> {
+        self.write_register_dump(dump).await
     }
 }
 }
@@ -1,47 +1,76 @@
 //! Functions exclusive of DS3232
 maybe_async_cfg::content! {
 #![maybe_async_cfg::default(
-    idents(I2cInterface, Ds323x),
+    idents(
+        I2cInterface, ReadData, WriteData, Ds323x,
+        embedded_hal(sync, async = "embedded_hal_async"),
+    ),
 )]
 
 #[maybe_async_cfg::maybe(
-    sync(not(feature = "async")),
+    sync(feature = "sync"),
     async(feature = "async")
 )]
 use crate::{
-    ic,BitFlags,interface::I2cInterface, Ds323x, Error, TempConvRate, CONTROL_POR_VALUE,
+    interface::{I2cInterface, ReadData, WriteData},
+    Ds323x,
 };
+use crate::{
+    ic, BitFlags, Config, Error, ExtendedConfig, Register, RegisterDump, TempConvRate,
+    CONTROL_POR_VALUE, SRAM_CHUNK_SIZE,
+};
+use crate::ds323x::{check_sram_range, DS3232_DUMP_LEN};
 use core::marker::PhantomData;
 
-#[cfg(not(feature = "async"))]
-use embedded_hal::i2c;
-#[cfg(feature = "async")]
-use embedded_hal_async::i2c;
+/// Size of the DS3232 battery-backed SRAM in bytes (registers 0x14-0xFF)
+pub(crate) const DS3232_SRAM_SIZE: usize = 236;
 
 #[maybe_async_cfg::maybe(
-    sync(not(feature = "async")),
+    sync(feature = "sync"),
     async(feature = "async")
 )]
 impl<I2C, E> Ds323x<I2cInterface<I2C>, ic::DS3232>
 where
-    I2C: i2c::I2c<Error = E>,
+    I2C: embedded_hal::i2c::I2c<Error = E>,
 {
     /// Create a new instance of the DS3232 device.
     pub fn new_ds3232(i2c: I2C) -> Self {
+        Ds323x::new_ds3232_with_interface(I2cInterface { i2c })
+    }
+
+    /// Destroy driver instance, return I²C bus instance.
+    pub fn destroy_ds3232(self) -> I2C {
+        self.iface.i2c
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+impl<DI> Ds323x<DI, ic::DS3232> {
+    /// Create a new instance of the DS3232 device with a custom interface.
+    ///
+    /// See the [`interface`](crate::interface) module documentation.
+    pub fn new_ds3232_with_interface(iface: DI) -> Self {
         const STATUS_POR_VALUE: u8 = BitFlags::OSC_STOP | BitFlags::BB32KHZ | BitFlags::EN32KHZ;
         Ds323x {
-            iface: I2cInterface { i2c },
+            iface,
             control: CONTROL_POR_VALUE,
             status: STATUS_POR_VALUE,
             _ic: PhantomData,
         }
     }
+}
 
-    /// Destroy driver instance, return I²C bus instance.
-    pub fn destroy_ds3232(self) -> I2C {
-        self.iface.i2c
-    }
-
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+impl<DI, E> Ds323x<DI, ic::DS3232>
+where
+    DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+{
     /// Enable the 32kHz output when battery-powered. (enabled per default)
     ///
     /// Additionally, the 32kHz output needs to be enabled. See
@@ -80,5 +109,103 @@ where
         };
         self.write_status_without_clearing_alarm(status).await
     }
+
+    /// Apply the whole configuration at once.
+    ///
+    /// The control, status and aging offset registers are written in a
+    /// single transaction. The alarm matched flags are not cleared.
+    ///
+    /// Note: The chip-specific [`ExtendedConfig`](struct.ExtendedConfig.html)
+    /// is only available for DS3232 devices.
+    pub async fn apply_config(&mut self, config: &Config<ExtendedConfig>) -> Result<(), Error<E>> {
+        self.write_config(config, config.extended.to_status()).await
+    }
+
+    /// Read back the whole configuration at once.
+    ///
+    /// The control, status and aging offset registers are read in a single
+    /// transaction.
+    pub async fn read_config(&mut self) -> Result<Config<ExtendedConfig>, Error<E>> {
+        self.read_config_with(ExtendedConfig::from_status).await
+    }
+
+    /// Read all the registers at once.
+    ///
+    /// The timekeeping, alarm, control, status, aging offset and temperature
+    /// registers (0x00-0x13) are read in a single transaction.
+    pub async fn dump_registers(&mut self) -> Result<RegisterDump, Error<E>> {
+        self.read_register_dump(DS3232_DUMP_LEN).await
+    }
+
+    /// Write back the configuration from a register dump.
+    ///
+    /// The alarm, control, status and aging offset registers are written in a
+    /// single transaction. The date and time are not restored and neither the
+    /// alarm matched flags nor the oscillator stop flag are modified.
+    pub async fn restore_registers(&mut self, dump: &RegisterDump) -> Result<(), Error<E>> {
+        self.write_register_dump(dump).await
+    }
+
+    /// Read data from the battery-backed SRAM starting at `offset`.
+    ///
+    /// The offset is relative to the beginning of the SRAM (register 0x14).
+    /// Will return an `Error::InvalidInputData` if the data does not fit in
+    /// the 236 bytes of SRAM. Long reads are split into several I²C transactions.
+    ///
+    /// Note: This is only available for DS3232 devices.
+    pub async fn read_sram(&mut self, offset: u8, data: &mut [u8]) -> Result<(), Error<E>> {
+        check_sram_range(offset, data.len(), DS3232_SRAM_SIZE)?;
+        let mut address = Register::SRAM_START.wrapping_add(offset);
+        for chunk in data.chunks_mut(SRAM_CHUNK_SIZE) {
+            let mut payload = [0; SRAM_CHUNK_SIZE + 1];
+            payload[0] = address;
+            let payload = &mut payload[..=chunk.len()];
+            self.iface.read_data(payload).await?;
+            chunk.copy_from_slice(&payload[1..]);
+            address = address.wrapping_add(chunk.len() as u8);
+        }
+        Ok(())
+    }
+
+    /// Write data to the battery-backed SRAM starting at `offset`.
+    ///
+    /// The offset is relative to the beginning of the SRAM (register 0x14).
+    /// Will return an `Error::InvalidInputData` if the data does not fit in
+    /// the 236 bytes of SRAM. Long writes are split into several I²C transactions.
+    ///
+    /// Note: This is only available for DS3232 devices.
+    pub async fn write_sram(&mut self, offset: u8, data: &[u8]) -> Result<(), Error<E>> {
+        check_sram_range(offset, data.len(), DS3232_SRAM_SIZE)?;
+        let mut address = Register::SRAM_START.wrapping_add(offset);
+        for chunk in data.chunks(SRAM_CHUNK_SIZE) {
+            let mut payload = [0; SRAM_CHUNK_SIZE + 1];
+            payload[0] = address;
+            payload[1..=chunk.len()].copy_from_slice(chunk);
+            self.iface.write_data(&mut payload[..=chunk.len()]).await?;
+            address = address.wrapping_add(chunk.len() as u8);
+        }
+        Ok(())
+    }
+
+    /// Set `len` bytes of the battery-backed SRAM starting at `offset` to `value`.
+    ///
+    /// Will return an `Error::InvalidInputData` without writing anything if
+    /// the range does not fit in the 236 bytes of SRAM. Long fills are split
+    /// into several I²C transactions.
+    ///
+    /// Note: This is only available for DS3232 devices.
+    pub async fn fill_sram(&mut self, offset: u8, len: usize, value: u8) -> Result<(), Error<E>> {
+        check_sram_range(offset, len, DS3232_SRAM_SIZE)?;
+        let data = [value; SRAM_CHUNK_SIZE];
+        let mut address = offset;
+        let mut remaining = len;
+        while remaining > 0 {
+            let count = remaining.min(SRAM_CHUNK_SIZE);
+            self.write_sram(address, &data[..count]).await?;
+            address = address.wrapping_add(count as u8);
+            remaining -= count;
+        }
+        Ok(())
+    }
 }
 }
@@ -2,45 +2,76 @@
 
 maybe_async_cfg::content! {
 #![maybe_async_cfg::default(
-    idents(SpiInterface, WriteData, Ds323x),
+    idents(
+        SpiInterface, ReadData, WriteData, Ds323x,
+        embedded_hal(sync, async = "embedded_hal_async"),
+    ),
 )]
 
 #[maybe_async_cfg::maybe(
-    sync(not(feature = "async")),
+    sync(feature = "sync"),
     async(feature = "async")
 )]
-use crate::{ic, interface::{SpiInterface, WriteData},Ds323x, BitFlags, Error, Register, TempConvRate, CONTROL_POR_VALUE};
+use crate::{
+    interface::{ReadData, SpiInterface, WriteData},
+    Ds323x,
+};
+use crate::{
+    ic, BitFlags, Config, Ds3234ExtendedConfig, Error, Register, RegisterDump, TempConvRate,
+    CONTROL_POR_VALUE, SRAM_CHUNK_SIZE,
+};
+use crate::ds323x::{check_sram_range, DS3234_DUMP_LEN};
 use core::marker::PhantomData;
 
-#[cfg(not(feature = "async"))]
-use embedded_hal::spi;
-#[cfg(feature = "async")]
-use embedded_hal_async::spi;
+/// Size of the DS3234 battery-backed SRAM in bytes
+pub(crate) const DS3234_SRAM_SIZE: usize = 256;
 
 #[maybe_async_cfg::maybe(
-    sync(not(feature = "async")),
+    sync(feature = "sync"),
     async(feature = "async")
 )]
 impl<SPI, E> Ds323x<SpiInterface<SPI>, ic::DS3234>
 where
-    SPI: spi::SpiDevice<u8, Error = E>,
+    SPI: embedded_hal::spi::SpiDevice<u8, Error = E>,
 {
     /// Create a new instance.
     pub fn new_ds3234(spi: SPI) -> Self {
+        Ds323x::new_ds3234_with_interface(SpiInterface { spi })
+    }
+
+    /// Destroy driver instance, return SPI bus instance and CS output pin.
+    pub fn destroy_ds3234(self) -> SPI {
+        self.iface.spi
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+impl<DI> Ds323x<DI, ic::DS3234> {
+    /// Create a new instance of the DS3234 device with a custom interface.
+    ///
+    /// See the [`interface`](crate::interface) module documentation.
+    pub fn new_ds3234_with_interface(iface: DI) -> Self {
         const STATUS_POR_VALUE: u8 = BitFlags::OSC_STOP | BitFlags::BB32KHZ | BitFlags::EN32KHZ;
         Ds323x {
-            iface: SpiInterface { spi },
+            iface,
             control: CONTROL_POR_VALUE,
             status: STATUS_POR_VALUE,
             _ic: PhantomData,
         }
     }
+}
 
-    /// Destroy driver instance, return SPI bus instance and CS output pin.
-    pub fn destroy_ds3234(self) -> SPI {
-        self.iface.spi
-    }
-
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+impl<DI, E> Ds323x<DI, ic::DS3234>
+where
+    DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+{
     /// Enable the 32kHz output when battery-powered. (enabled per default)
     ///
     /// Additionally, the 32kHz output needs to be enabled. See
@@ -80,6 +111,65 @@ where
         self.write_status_without_clearing_alarm(status).await
     }
 
+    /// Apply the whole configuration at once.
+    ///
+    /// The control, status and aging offset registers are written in a
+    /// single transaction. The alarm matched flags are not cleared. The
+    /// temperature conversion register is then written separately.
+    ///
+    /// Note: The chip-specific
+    /// [`Ds3234ExtendedConfig`](struct.Ds3234ExtendedConfig.html) is only
+    /// available for DS3234 devices.
+    pub async fn apply_config(&mut self, config: &Config<Ds3234ExtendedConfig>) -> Result<(), Error<E>> {
+        self.write_config(config, config.extended.to_status()).await?;
+        self.iface
+            .write_register(Register::TEMP_CONV, config.extended.to_temperature_conversion())
+            .await
+    }
+
+    /// Read back the whole configuration at once.
+    ///
+    /// The control, status and aging offset registers are read in a single
+    /// transaction. The temperature conversion register is then read
+    /// separately.
+    pub async fn read_config(&mut self) -> Result<Config<Ds3234ExtendedConfig>, Error<E>> {
+        let config = self.read_config_with(|status| status).await?;
+        let temperature_conversion = self.iface.read_register(Register::TEMP_CONV).await?;
+        Ok(Config {
+            control: config.control,
+            enabled_32khz_output: config.enabled_32khz_output,
+            aging_offset: config.aging_offset,
+            extended: Ds3234ExtendedConfig::from_registers(config.extended, temperature_conversion),
+        })
+    }
+
+    /// Read all the registers at once.
+    ///
+    /// The timekeeping, alarm, control, status, aging offset and temperature
+    /// registers (0x00-0x13) as well as the SRAM address and data registers
+    /// (0x18-0x19) are read in a single transaction. Note that reading the
+    /// SRAM data register advances the SRAM address.
+    pub async fn dump_registers(&mut self) -> Result<RegisterDump, Error<E>> {
+        self.read_register_dump(DS3234_DUMP_LEN).await
+    }
+
+    /// Write back the configuration from a register dump.
+    ///
+    /// The alarm, control, status and aging offset registers are written in a
+    /// single transaction. The date and time are not restored and neither the
+    /// alarm matched flags nor the oscillator stop flag are modified. The
+    /// temperature conversion register is then written separately.
+    pub async fn restore_registers(&mut self, dump: &RegisterDump) -> Result<(), Error<E>> {
+        self.write_register_dump(dump).await?;
+        if let Some(temperature_conversion) = dump.temperature_conversion {
+            let temperature_conversion = temperature_conversion & BitFlags::TEMP_CONV_BAT;
+            self.iface
+                .write_register(Register::TEMP_CONV, temperature_conversion)
+                .await?;
+        }
+        Ok(())
+    }
+
     /// Enable the temperature conversions when battery-powered. (enabled per default)
     ///
     /// Note: This is only available for DS3234 devices.
@@ -95,5 +185,76 @@ where
             .write_register(Register::TEMP_CONV, BitFlags::TEMP_CONV_BAT)
             .await
     }
+
+    /// Read data from the battery-backed SRAM starting at `offset`.
+    ///
+    /// The SRAM address register is set once and the data is then streamed
+    /// through the auto-incrementing SRAM data register. Long reads are split
+    /// into several SPI transactions.
+    ///
+    /// Will return an `Error::InvalidInputData` if the data does not fit in
+    /// the 256 bytes of SRAM.
+    ///
+    /// Note: This is only available for DS3234 devices.
+    pub async fn read_sram(&mut self, offset: u8, data: &mut [u8]) -> Result<(), Error<E>> {
+        check_sram_range(offset, data.len(), DS3234_SRAM_SIZE)?;
+        if data.is_empty() {
+            return Ok(());
+        }
+        self.iface.write_register(Register::SRAM_ADDRESS, offset).await?;
+        for chunk in data.chunks_mut(SRAM_CHUNK_SIZE) {
+            let mut payload = [0; SRAM_CHUNK_SIZE + 1];
+            payload[0] = Register::SRAM_DATA;
+            let payload = &mut payload[..=chunk.len()];
+            self.iface.read_data(payload).await?;
+            chunk.copy_from_slice(&payload[1..]);
+        }
+        Ok(())
+    }
+
+    /// Write data to the battery-backed SRAM starting at `offset`.
+    ///
+    /// The SRAM address and the data are sent in a single SPI transaction
+    /// starting at the SRAM address register. Long writes are split into
+    /// several SPI transactions.
+    ///
+    /// Will return an `Error::InvalidInputData` if the data does not fit in
+    /// the 256 bytes of SRAM.
+    ///
+    /// Note: This is only available for DS3234 devices.
+    pub async fn write_sram(&mut self, offset: u8, data: &[u8]) -> Result<(), Error<E>> {
+        check_sram_range(offset, data.len(), DS3234_SRAM_SIZE)?;
+        let mut address = offset;
+        for chunk in data.chunks(SRAM_CHUNK_SIZE) {
+            let mut payload = [0; SRAM_CHUNK_SIZE + 2];
+            payload[0] = Register::SRAM_ADDRESS;
+            payload[1] = address;
+            payload[2..chunk.len() + 2].copy_from_slice(chunk);
+            self.iface.write_data(&mut payload[..chunk.len() + 2]).await?;
+            address = address.wrapping_add(chunk.len() as u8);
+        }
+        Ok(())
+    }
+
+    /// Set `len` bytes of the battery-backed SRAM starting at `offset` to `value`.
+    ///
+    /// Will return an `Error::InvalidInputData` without writing anything if
+    /// the range does not fit in the 256 bytes of SRAM. Long fills are split
+    /// into several SPI transactions.
+    ///
+    /// Note: This is only available for DS3234 devices.
+    pub async fn fill_sram(&mut self, offset: u8, len: usize, value: u8) -> Result<(), Error<E>> {
+        check_sram_range(offset, len, DS3234_SRAM_SIZE)?;
+        let data = [value; SRAM_CHUNK_SIZE];
+        let mut address = offset;
+        let mut remaining = len;
+        while remaining > 0 {
+            let count = remaining.min(SRAM_CHUNK_SIZE);
+            self.write_sram(address, &data[..count]).await?;
+            address = address.wrapping_add(count as u8);
+            remaining -= count;
+        }
+        Ok(())
+    }
 }
 }
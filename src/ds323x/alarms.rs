@@ -2,19 +2,30 @@
 //!
 maybe_async_cfg::content! {
 #![maybe_async_cfg::default(
-    idents(ReadData, WriteData, Ds323x),
+    idents(
+        ReadData, WriteData, Ds323x,
+        DateTimeAccess(sync, async = "AsyncDateTimeAccess"),
+    ),
 )]
 
-use super::{decimal_to_packed_bcd, hours_to_register};
+use super::{
+    decimal_to_packed_bcd, hours_from_register, hours_to_register,
+    occurrence::round_up_to_minute,
+    packed_bcd_to_decimal, some_or_invalid_error,
+};
 #[maybe_async_cfg::maybe(
-    sync(not(feature = "async")),
+    sync(feature = "sync"),
     async(feature = "async")
 )]
 use crate::{
     interface::{ReadData, WriteData},
-    ds323x::{NaiveTime, Timelike},
-    BitFlags, Ds323x, Error, Hours, Register,
+    DateTimeAccess, Ds323x,
+};
+use crate::{
+    BitFlags, Datelike, Error, Hours, NaiveDate, NaiveDateTime, NaiveTime, Register, Schedule,
+    Timelike,
 };
+use core::{convert::TryFrom, time::Duration};
 
 /// Parameters for setting Alarm1 on a day of the month
 ///
@@ -36,6 +47,18 @@ pub struct DayAlarm1 {
     pub second: u8,
 }
 
+impl DayAlarm1 {
+    /// Alarm for a time on a day of the month in 24h format.
+    pub(crate) fn at(day: u8, time: NaiveTime) -> Self {
+        DayAlarm1 {
+            day,
+            hour: Hours::H24(time.hour() as u8),
+            minute: time.minute() as u8,
+            second: time.second() as u8,
+        }
+    }
+}
+
 /// Parameters for setting Alarm1 on a weekday
 ///
 /// Depending on the matching strategy, some fields may not be relevant. In this
@@ -90,6 +113,19 @@ pub struct DayAlarm2 {
     pub minute: u8,
 }
 
+impl DayAlarm2 {
+    /// Alarm for a time on a day of the month in 24h format.
+    ///
+    /// The seconds are ignored.
+    pub(crate) fn at(day: u8, time: NaiveTime) -> Self {
+        DayAlarm2 {
+            day,
+            hour: Hours::H24(time.hour() as u8),
+            minute: time.minute() as u8,
+        }
+    }
+}
+
 /// Parameters for setting Alarm2 on a weekday
 ///
 /// Depending on the matching strategy, some fields may not be relevant. In this
@@ -122,6 +158,26 @@ pub enum Alarm2Matching {
     AllMatch,
 }
 
+/// Alarm1 configuration as programmed in the device
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Alarm1Config {
+    /// Alarm set for a day of the month
+    Day(DayAlarm1, Alarm1Matching),
+    /// Alarm set for a weekday
+    Weekday(WeekdayAlarm1, Alarm1Matching),
+}
+
+/// Alarm2 configuration as programmed in the device
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Alarm2Config {
+    /// Alarm set for a day of the month
+    Day(DayAlarm2, Alarm2Matching),
+    /// Alarm set for a weekday
+    Weekday(WeekdayAlarm2, Alarm2Matching),
+}
+
 fn get_matching_mask_alarm1(matching: Alarm1Matching) -> [u8; 4] {
     const AM: u8 = BitFlags::ALARM_MATCH;
     match matching {
@@ -143,6 +199,37 @@ fn get_matching_mask_alarm2(matching: Alarm2Matching) -> [u8; 3] {
     }
 }
 
+fn get_alarm1_matching<E>(data: &[u8]) -> Result<Alarm1Matching, Error<E>> {
+    const AM: u8 = BitFlags::ALARM_MATCH;
+    let mask = [data[0] & AM, data[1] & AM, data[2] & AM, data[3] & AM];
+    [
+        Alarm1Matching::OncePerSecond,
+        Alarm1Matching::SecondsMatch,
+        Alarm1Matching::MinutesAndSecondsMatch,
+        Alarm1Matching::HoursMinutesAndSecondsMatch,
+        Alarm1Matching::AllMatch,
+    ]
+    .iter()
+    .copied()
+    .find(|matching| get_matching_mask_alarm1(*matching) == mask)
+    .ok_or(Error::InvalidDeviceState)
+}
+
+fn get_alarm2_matching<E>(data: &[u8]) -> Result<Alarm2Matching, Error<E>> {
+    const AM: u8 = BitFlags::ALARM_MATCH;
+    let mask = [data[0] & AM, data[1] & AM, data[2] & AM];
+    [
+        Alarm2Matching::OncePerMinute,
+        Alarm2Matching::MinutesMatch,
+        Alarm2Matching::HoursAndMinutesMatch,
+        Alarm2Matching::AllMatch,
+    ]
+    .iter()
+    .copied()
+    .find(|matching| get_matching_mask_alarm2(*matching) == mask)
+    .ok_or(Error::InvalidDeviceState)
+}
+
 /// Test if hour value is valid
 fn is_hour_valid(hours: Hours) -> bool {
     match hours {
@@ -153,6 +240,22 @@ fn is_hour_valid(hours: Hours) -> bool {
     }
 }
 
+/// Add an interval rounded up to whole seconds. Zero intervals are rejected.
+fn add_interval(datetime: NaiveDateTime, interval: Duration) -> Option<NaiveDateTime> {
+    let mut seconds = interval.as_secs();
+    if interval.subsec_nanos() != 0 {
+        seconds = seconds.checked_add(1)?;
+    }
+    if seconds == 0 {
+        return None;
+    }
+    let seconds = u64::from(datetime.num_seconds_from_midnight()).checked_add(seconds)?;
+    let days = i32::try_from(seconds / 86_400).ok()?;
+    let date = NaiveDate::from_num_days_from_ce_opt(datetime.num_days_from_ce().checked_add(days)?)?;
+    let time = NaiveTime::from_num_seconds_from_midnight_opt((seconds % 86_400) as u32, 0)?;
+    Some(date.and_time(time))
+}
+
 /// Amend invalid hour values
 fn amend_hour(hours: Hours) -> Hours {
     match hours {
@@ -166,7 +269,7 @@ fn amend_hour(hours: Hours) -> Hours {
 }
 
 #[maybe_async_cfg::maybe(
-    sync(not(feature = "async")),
+    sync(feature = "sync"),
     async(feature = "async")
 )]
 impl<DI, IC, E> Ds323x<DI, IC>
@@ -220,12 +323,7 @@ where
     /// Will return an `Error::InvalidInputData` if any of the parameters is out of range.
     /// The day is not used by this matching strategy and is set to 1.
     pub async fn set_alarm1_hms(&mut self, when: NaiveTime) -> Result<(), Error<E>> {
-        let alarm = DayAlarm1 {
-            day: 1,
-            hour: Hours::H24(when.hour() as u8),
-            minute: when.minute() as u8,
-            second: when.second() as u8,
-        };
+        let alarm = DayAlarm1::at(1, when);
         self.set_alarm1_day(alarm, Alarm1Matching::HoursMinutesAndSecondsMatch).await
     }
 
@@ -271,6 +369,59 @@ where
         self.iface.write_data(&mut data).await
     }
 
+    /// Set Alarm1 to fire at a date and time.
+    ///
+    /// The alarm is programmed for the day of the month, hours, minutes and
+    /// seconds of `when`. Any fraction of a second is ignored.
+    ///
+    /// Will return an `Error::InvalidInputData` if `when` is not in the future
+    /// or is too far in the future for the alarm to match it next. That is,
+    /// if the same day of the month and time will be reached earlier.
+    ///
+    /// Note: The alarm will match again on the next month with that day
+    /// unless the alarm interrupts are disabled or the alarm is reprogrammed.
+    pub async fn set_alarm1_at(&mut self, when: NaiveDateTime) -> Result<(), Error<E>> {
+        let now = self.datetime().await?;
+        self.set_alarm1_at_from(now, when).await
+    }
+
+    /// Set Alarm1 to fire after some time has elapsed.
+    ///
+    /// Reads the current date and time, adds `interval` to it and programs
+    /// the alarm for the resulting date and time, which is returned.
+    /// The interval is rounded up to whole seconds. Since the device does not
+    /// provide the fraction of the current second, the alarm may fire up to
+    /// one second earlier than `interval`.
+    ///
+    /// Will return an `Error::InvalidInputData` if `interval` is zero or too
+    /// long for the alarm to match the target next (see
+    /// [`set_alarm1_at()`](#method.set_alarm1_at)).
+    ///
+    /// The date and time is read again after programming the alarm. Will return
+    /// an `Error::AlarmMissed` if the target has been reached in the meantime.
+    /// This includes the case where the current time is the target, since the
+    /// device only compares the alarms when the time is updated.
+    pub async fn set_alarm1_in(&mut self, interval: Duration) -> Result<NaiveDateTime, Error<E>> {
+        let now = self.datetime().await?;
+        let when = add_interval(now, interval).ok_or(Error::InvalidInputData)?;
+        self.set_alarm1_at_from(now, when).await?;
+        self.check_alarm_not_missed(when).await?;
+        Ok(when)
+    }
+
+    async fn set_alarm1_at_from(
+        &mut self,
+        now: NaiveDateTime,
+        when: NaiveDateTime,
+    ) -> Result<(), Error<E>> {
+        let alarm = DayAlarm1::at(when.day() as u8, when.time());
+        let pattern = Schedule::from_alarm1(Alarm1Config::Day(alarm, Alarm1Matching::AllMatch));
+        if pattern.next_after(now) != when.with_nanosecond(0) {
+            return Err(Error::InvalidInputData);
+        }
+        self.set_alarm1_day(alarm, Alarm1Matching::AllMatch).await
+    }
+
     /// Set Alarm2 for date (day of month).
     ///
     /// Will return an `Error::InvalidInputData` if any of the used parameters
@@ -313,11 +464,7 @@ where
     /// Will return an `Error::InvalidInputData` if any of the parameters is out of range.
     /// The day is not used by this matching strategy and is set to 1.
     pub async fn set_alarm2_hm(&mut self, when: NaiveTime) -> Result<(), Error<E>> {
-        let alarm = DayAlarm2 {
-            day: 1,
-            hour: Hours::H24(when.hour() as u8),
-            minute: when.minute() as u8,
-        };
+        let alarm = DayAlarm2::at(1, when);
         self.set_alarm2_day(alarm, Alarm2Matching::HoursAndMinutesMatch).await
     }
 
@@ -356,5 +503,184 @@ where
         ];
         self.iface.write_data(&mut data).await
     }
+
+    /// Set Alarm2 to fire at a date and time.
+    ///
+    /// The alarm is programmed for the day of the month, hours and minutes
+    /// of `when`.
+    ///
+    /// Will return an `Error::InvalidInputData` if `when` has non-zero seconds,
+    /// since Alarm2 always matches at 00 seconds, if it is not in the future
+    /// or if it is too far in the future for the alarm to match it next.
+    /// See [`set_alarm2_at_rounded()`](#method.set_alarm2_at_rounded) to
+    /// round up to the next minute instead.
+    ///
+    /// Note: The alarm will match again on the next month with that day
+    /// unless the alarm interrupts are disabled or the alarm is reprogrammed.
+    pub async fn set_alarm2_at(&mut self, when: NaiveDateTime) -> Result<(), Error<E>> {
+        let now = self.datetime().await?;
+        self.set_alarm2_at_from(now, when).await
+    }
+
+    async fn set_alarm2_at_from(
+        &mut self,
+        now: NaiveDateTime,
+        when: NaiveDateTime,
+    ) -> Result<(), Error<E>> {
+        if when.second() != 0 || when.nanosecond() != 0 {
+            return Err(Error::InvalidInputData);
+        }
+        let alarm = DayAlarm2::at(when.day() as u8, when.time());
+        let pattern = Schedule::from_alarm2(Alarm2Config::Day(alarm, Alarm2Matching::AllMatch));
+        if pattern.next_after(now) != Some(when) {
+            return Err(Error::InvalidInputData);
+        }
+        self.set_alarm2_day(alarm, Alarm2Matching::AllMatch).await
+    }
+
+    /// Set Alarm2 to fire at a date and time rounded up to the next minute.
+    ///
+    /// Same as [`set_alarm2_at()`](#method.set_alarm2_at) but a `when` with
+    /// non-zero seconds is rounded up to the next minute so that the alarm
+    /// never fires earlier than requested.
+    ///
+    /// Returns the date and time for which the alarm has been programmed.
+    pub async fn set_alarm2_at_rounded(
+        &mut self,
+        when: NaiveDateTime,
+    ) -> Result<NaiveDateTime, Error<E>> {
+        let when = round_up_to_minute(when).ok_or(Error::InvalidInputData)?;
+        self.set_alarm2_at(when).await?;
+        Ok(when)
+    }
+
+    /// Set Alarm2 to fire after some time has elapsed.
+    ///
+    /// Reads the current date and time, adds `interval` to it and programs
+    /// the alarm for the resulting date and time rounded up to the next
+    /// minute, which is returned.
+    ///
+    /// Will return an `Error::InvalidInputData` if `interval` is zero or too
+    /// long for the alarm to match the target next (see
+    /// [`set_alarm2_at()`](#method.set_alarm2_at)).
+    ///
+    /// The date and time is read again after programming the alarm. Will return
+    /// an `Error::AlarmMissed` if the target has been reached in the meantime.
+    /// This includes the case where the current time is the target, since the
+    /// device only compares the alarms when the time is updated.
+    pub async fn set_alarm2_in(&mut self, interval: Duration) -> Result<NaiveDateTime, Error<E>> {
+        let now = self.datetime().await?;
+        let when = add_interval(now, interval)
+            .and_then(round_up_to_minute)
+            .ok_or(Error::InvalidInputData)?;
+        self.set_alarm2_at_from(now, when).await?;
+        self.check_alarm_not_missed(when).await?;
+        Ok(when)
+    }
+
+    // An alarm for the current time is missed as well: the device compares the
+    // alarms when the time is updated, so it only matches on the next occurrence.
+    pub(super) async fn check_alarm_not_missed(&mut self, when: NaiveDateTime) -> Result<(), Error<E>> {
+        if self.datetime().await? < when {
+            Ok(())
+        } else {
+            Err(Error::AlarmMissed)
+        }
+    }
+
+    /// Read the Alarm1 configuration.
+    ///
+    /// The values of the fields not used by the matching strategy are
+    /// returned as stored in the device.
+    ///
+    /// Will return an `Error::InvalidDeviceState` if the alarm mask bits do
+    /// not correspond to any matching strategy.
+    pub async fn alarm1(&mut self) -> Result<Alarm1Config, Error<E>> {
+        const AM: u8 = BitFlags::ALARM_MATCH;
+        let mut data = [Register::ALARM1_SECONDS, 0, 0, 0, 0];
+        self.iface.read_data(&mut data).await?;
+        let matching = get_alarm1_matching(&data[1..])?;
+        let second = packed_bcd_to_decimal(data[1] & !AM);
+        let minute = packed_bcd_to_decimal(data[2] & !AM);
+        let hour = hours_from_register(data[3] & !AM);
+        let day = data[4] & !AM;
+        if (day & BitFlags::WEEKDAY) != 0 {
+            let alarm = WeekdayAlarm1 {
+                weekday: packed_bcd_to_decimal(day & !BitFlags::WEEKDAY),
+                hour,
+                minute,
+                second,
+            };
+            Ok(Alarm1Config::Weekday(alarm, matching))
+        } else {
+            let alarm = DayAlarm1 {
+                day: packed_bcd_to_decimal(day),
+                hour,
+                minute,
+                second,
+            };
+            Ok(Alarm1Config::Day(alarm, matching))
+        }
+    }
+
+    /// Read the Alarm2 configuration.
+    ///
+    /// The values of the fields not used by the matching strategy are
+    /// returned as stored in the device.
+    ///
+    /// Will return an `Error::InvalidDeviceState` if the alarm mask bits do
+    /// not correspond to any matching strategy.
+    pub async fn alarm2(&mut self) -> Result<Alarm2Config, Error<E>> {
+        const AM: u8 = BitFlags::ALARM_MATCH;
+        let mut data = [Register::ALARM2_MINUTES, 0, 0, 0];
+        self.iface.read_data(&mut data).await?;
+        let matching = get_alarm2_matching(&data[1..])?;
+        let minute = packed_bcd_to_decimal(data[1] & !AM);
+        let hour = hours_from_register(data[2] & !AM);
+        let day = data[3] & !AM;
+        if (day & BitFlags::WEEKDAY) != 0 {
+            let alarm = WeekdayAlarm2 {
+                weekday: packed_bcd_to_decimal(day & !BitFlags::WEEKDAY),
+                hour,
+                minute,
+            };
+            Ok(Alarm2Config::Weekday(alarm, matching))
+        } else {
+            let alarm = DayAlarm2 {
+                day: packed_bcd_to_decimal(day),
+                hour,
+                minute,
+            };
+            Ok(Alarm2Config::Day(alarm, matching))
+        }
+    }
+
+    /// Compute when Alarm1 will next match.
+    ///
+    /// Reads the current date and time and the Alarm1 configuration and
+    /// returns the first date and time strictly after now when the alarm
+    /// will match according to its matching strategy. Days of the month that
+    /// do not exist in a month (e.g. the 31st) are skipped like the device does.
+    /// Weekdays are numbered starting from Sunday = 1, as written by
+    /// `set_datetime()`.
+    ///
+    /// Will return an `Error::InvalidDeviceState` if the alarm can never match.
+    pub async fn next_alarm1_occurrence(&mut self) -> Result<NaiveDateTime, Error<E>> {
+        let alarm = self.alarm1().await?;
+        let now = self.datetime().await?;
+        some_or_invalid_error(Schedule::from_alarm1(alarm).next_after(now))
+    }
+
+    /// Compute when Alarm2 will next match.
+    ///
+    /// Same as [`next_alarm1_occurrence()`](#method.next_alarm1_occurrence)
+    /// for Alarm2, which always matches at 00 seconds.
+    ///
+    /// Will return an `Error::InvalidDeviceState` if the alarm can never match.
+    pub async fn next_alarm2_occurrence(&mut self) -> Result<NaiveDateTime, Error<E>> {
+        let alarm = self.alarm2().await?;
+        let now = self.datetime().await?;
+        some_or_invalid_error(Schedule::from_alarm2(alarm).next_after(now))
+    }
 }
 }
@@ -5,20 +5,199 @@ maybe_async_cfg::content! {
 )]
 
 #[maybe_async_cfg::maybe(
-    sync(not(feature = "async")),
+    sync(feature = "sync"),
     async(feature = "async")
 )]
-use crate::interface::{ReadData, WriteData};
-#[maybe_async_cfg::maybe(
-    sync(not(feature = "async")),
-    async(feature = "async")
-)]
-use crate::{BitFlags, Ds323x, Error, Register, SqWFreq};
+use crate::{
+    interface::{ReadData, WriteData},
+    Ds323x,
+};
+use crate::{BitFlags, Error, Register, SqWFreq, Status, TempConvRate};
 
+/// Function of the INT/SQW output pin
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum IntSqwOutput {
+    /// Interrupt output
+    Interrupt,
+    /// Square-wave output
+    SquareWave,
+}
 
+/// Decoded control register
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Control {
+    /// The oscillator is enabled (EOSC cleared)
+    pub oscillator_enabled: bool,
+    /// Square-wave generation is enabled when battery-powered (BBSQW)
+    pub square_wave_on_battery: bool,
+    /// A temperature conversion is forced (CONV)
+    ///
+    /// Setting this with [`set_control()`](struct.Ds323x.html#method.set_control)
+    /// starts a temperature conversion.
+    pub convert_temperature: bool,
+    /// Square-wave output frequency (RS2 and RS1)
+    pub square_wave_frequency: SqWFreq,
+    /// Function of the INT/SQW output pin (INTCN)
+    pub int_sqw_output: IntSqwOutput,
+    /// Alarm2 interrupts are enabled (A2IE)
+    pub alarm2_interrupts_enabled: bool,
+    /// Alarm1 interrupts are enabled (A1IE)
+    pub alarm1_interrupts_enabled: bool,
+}
+
+impl Control {
+    fn from_register(control: u8) -> Self {
+        let square_wave_frequency =
+            match (control & BitFlags::RS2 != 0, control & BitFlags::RS1 != 0) {
+                (false, false) => SqWFreq::_1Hz,
+                (false, true) => SqWFreq::_1_024Hz,
+                (true, false) => SqWFreq::_4_096Hz,
+                (true, true) => SqWFreq::_8_192Hz,
+            };
+        let int_sqw_output = if (control & BitFlags::INTCN) != 0 {
+            IntSqwOutput::Interrupt
+        } else {
+            IntSqwOutput::SquareWave
+        };
+        Control {
+            oscillator_enabled: (control & BitFlags::EOSC) == 0,
+            square_wave_on_battery: (control & BitFlags::BBSQW) != 0,
+            convert_temperature: (control & BitFlags::TEMP_CONV) != 0,
+            square_wave_frequency,
+            int_sqw_output,
+            alarm2_interrupts_enabled: (control & BitFlags::ALARM2_INT_EN) != 0,
+            alarm1_interrupts_enabled: (control & BitFlags::ALARM1_INT_EN) != 0,
+        }
+    }
+
+    fn to_register(self) -> u8 {
+        let mut control = match self.square_wave_frequency {
+            SqWFreq::_1Hz => 0,
+            SqWFreq::_1_024Hz => BitFlags::RS1,
+            SqWFreq::_4_096Hz => BitFlags::RS2,
+            SqWFreq::_8_192Hz => BitFlags::RS2 | BitFlags::RS1,
+        };
+        if !self.oscillator_enabled {
+            control |= BitFlags::EOSC;
+        }
+        if self.square_wave_on_battery {
+            control |= BitFlags::BBSQW;
+        }
+        if self.convert_temperature {
+            control |= BitFlags::TEMP_CONV;
+        }
+        if self.int_sqw_output == IntSqwOutput::Interrupt {
+            control |= BitFlags::INTCN;
+        }
+        if self.alarm2_interrupts_enabled {
+            control |= BitFlags::ALARM2_INT_EN;
+        }
+        if self.alarm1_interrupts_enabled {
+            control |= BitFlags::ALARM1_INT_EN;
+        }
+        control
+    }
+}
+
+/// Complete device configuration
+///
+/// Applied with a single bus transaction with `apply_config()` and read
+/// back with `read_config()`. The chip-specific configuration is in
+/// `extended`, which is [`ExtendedConfig`] for the DS3232,
+/// [`Ds3234ExtendedConfig`] for the DS3234 and `()` for the DS3231.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Config<X = ()> {
+    /// Control register configuration
+    ///
+    /// Setting `convert_temperature` starts a temperature conversion.
+    pub control: Control,
+    /// The 32kHz output is enabled
+    pub enabled_32khz_output: bool,
+    /// Aging offset
+    pub aging_offset: i8,
+    /// Chip-specific configuration
+    pub extended: X,
+}
+
+/// Configuration only available on the DS3232
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ExtendedConfig {
+    /// The 32kHz output is enabled when battery-powered
+    pub enabled_32khz_output_on_battery: bool,
+    /// Temperature conversion rate
+    pub temperature_conversion_rate: TempConvRate,
+}
+
+impl ExtendedConfig {
+    pub(crate) fn from_status(status: u8) -> Self {
+        let status = Status::from_register(status);
+        ExtendedConfig {
+            enabled_32khz_output_on_battery: status.enabled_32khz_output_on_battery,
+            temperature_conversion_rate: status.temperature_conversion_rate,
+        }
+    }
+
+    pub(crate) fn to_status(self) -> u8 {
+        let mut status = match self.temperature_conversion_rate {
+            TempConvRate::_64s => 0,
+            TempConvRate::_128s => BitFlags::CRATE0,
+            TempConvRate::_256s => BitFlags::CRATE1,
+            TempConvRate::_512s => BitFlags::CRATE1 | BitFlags::CRATE0,
+        };
+        if self.enabled_32khz_output_on_battery {
+            status |= BitFlags::BB32KHZ;
+        }
+        status
+    }
+}
+
+/// Configuration only available on the DS3234
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Ds3234ExtendedConfig {
+    /// The 32kHz output is enabled when battery-powered
+    pub enabled_32khz_output_on_battery: bool,
+    /// Temperature conversion rate
+    pub temperature_conversion_rate: TempConvRate,
+    /// Temperature conversions are enabled when battery-powered (BB_TD cleared)
+    pub enabled_temperature_conversions_on_battery: bool,
+}
+
+impl Ds3234ExtendedConfig {
+    pub(crate) fn from_registers(status: u8, temperature_conversion: u8) -> Self {
+        let extended = ExtendedConfig::from_status(status);
+        Ds3234ExtendedConfig {
+            enabled_32khz_output_on_battery: extended.enabled_32khz_output_on_battery,
+            temperature_conversion_rate: extended.temperature_conversion_rate,
+            enabled_temperature_conversions_on_battery: (temperature_conversion
+                & BitFlags::TEMP_CONV_BAT)
+                == 0,
+        }
+    }
+
+    pub(crate) fn to_status(self) -> u8 {
+        ExtendedConfig {
+            enabled_32khz_output_on_battery: self.enabled_32khz_output_on_battery,
+            temperature_conversion_rate: self.temperature_conversion_rate,
+        }
+        .to_status()
+    }
+
+    pub(crate) fn to_temperature_conversion(self) -> u8 {
+        if self.enabled_temperature_conversions_on_battery {
+            0
+        } else {
+            BitFlags::TEMP_CONV_BAT
+        }
+    }
+}
 
 #[maybe_async_cfg::maybe(
-    sync(not(feature = "async")),
+    sync(feature = "sync"),
     async(feature = "async")
 )]
 impl<DI, IC, E> Ds323x<DI, IC>
@@ -136,14 +315,83 @@ where
         self.write_control(control & !BitFlags::ALARM2_INT_EN).await
     }
 
+    /// Read and decode the whole control register at once.
+    pub async fn control(&mut self) -> Result<Control, Error<E>> {
+        let control = self.iface.read_register(Register::CONTROL).await?;
+        Ok(Control::from_register(control))
+    }
+
+    /// Write all the fields of the control register at once.
+    pub async fn set_control(&mut self, control: Control) -> Result<(), Error<E>> {
+        self.write_control(control.to_register()).await
+    }
+
+    /// Read the control and status registers in a single transaction and use
+    /// them as the cached configuration.
+    ///
+    /// The driver assumes the power-on reset values of these registers when
+    /// it is created. This should be called after creating it for a device
+    /// that kept running on battery (e.g. after a reset of the MCU) so that
+    /// subsequent configuration changes preserve the current configuration.
+    pub async fn sync_from_device(&mut self) -> Result<(), Error<E>> {
+        let mut data = [Register::CONTROL, 0, 0];
+        self.iface.read_data(&mut data).await?;
+        // do not start a conversion or clear the alarm flags on later writes
+        self.control = data[1] & !BitFlags::TEMP_CONV;
+        self.status = data[2] & !(BitFlags::BUSY | BitFlags::ALARM2F | BitFlags::ALARM1F);
+        Ok(())
+    }
+
     async fn write_control(&mut self, control: u8) -> Result<(), Error<E>> {
         self.iface
             .write_register(Register::CONTROL, control)
             .await?;
-        self.control = control;
+        // do not start a conversion again on later writes
+        self.control = control & !BitFlags::TEMP_CONV;
+        Ok(())
+    }
+
+    // Writes the control, status and aging offset registers in one transaction.
+    // `extended_status` contains the chip-specific status bits.
+    pub(crate) async fn write_config<X>(
+        &mut self,
+        config: &Config<X>,
+        extended_status: u8,
+    ) -> Result<(), Error<E>> {
+        let control = config.control.to_register();
+        let mut status = (self.status & BitFlags::OSC_STOP) | extended_status;
+        if config.enabled_32khz_output {
+            status |= BitFlags::EN32KHZ;
+        }
+        // avoid clearing alarm flags
+        let mut payload = [
+            Register::CONTROL,
+            control,
+            status | BitFlags::ALARM2F | BitFlags::ALARM1F,
+            config.aging_offset as u8,
+        ];
+        self.iface.write_data(&mut payload).await?;
+        self.control = control & !BitFlags::TEMP_CONV;
+        self.status = status;
         Ok(())
     }
 
+    // Reads the control, status and aging offset registers in one transaction.
+    // The chip-specific configuration is decoded from the status by `extended`.
+    pub(crate) async fn read_config_with<X>(
+        &mut self,
+        extended: impl FnOnce(u8) -> X,
+    ) -> Result<Config<X>, Error<E>> {
+        let mut data = [Register::CONTROL, 0, 0, 0];
+        self.iface.read_data(&mut data).await?;
+        Ok(Config {
+            control: Control::from_register(data[1]),
+            enabled_32khz_output: (data[2] & BitFlags::EN32KHZ) != 0,
+            aging_offset: data[3] as i8,
+            extended: extended(data[2]),
+        })
+    }
+
     pub(crate) async fn write_status_without_clearing_alarm(
         &mut self,
         status: u8,
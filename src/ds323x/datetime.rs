@@ -1,33 +1,31 @@
 //! Common implementation
 maybe_async_cfg::content! {
 #![maybe_async_cfg::default(
-    idents(ReadData, WriteData, Ds323x),
+    idents(
+        ReadData, WriteData, Ds323x,
+        DateTimeAccess(sync, async = "AsyncDateTimeAccess"),
+        Rtcc(sync, async = "AsyncRtcc"),
+    ),
 )]
 use super::{
-    decimal_to_packed_bcd, hours_to_register, packed_bcd_to_decimal, some_or_invalid_error,
+    decimal_to_packed_bcd, hours_from_register, hours_to_register, packed_bcd_to_decimal,
+    some_or_invalid_error,
 };
 
 #[maybe_async_cfg::maybe(
-    sync(not(feature = "async")),
+    sync(feature = "sync"),
     async(feature = "async")
 )]
 use crate::{
     interface::{ReadData, WriteData},
-    BitFlags, Datelike, Ds323x, Error, Hours, NaiveDate, NaiveDateTime, NaiveTime,
-    Register, Timelike,
+    DateTimeAccess, Ds323x, Rtcc,
+};
+use crate::{
+    BitFlags, Datelike, Error, Hours, NaiveDate, NaiveDateTime, NaiveTime, Register, Timelike,
 };
-
-#[cfg(not(feature = "async"))]
-use rtcc::Rtcc;
-#[cfg(not(feature = "async"))]
-use crate::DateTimeAccess;
-#[cfg(feature = "async")]
-use super::rtcc_async::Rtcc;
-#[cfg(feature = "async")]
-use crate::DateTimeAccess;
 
 #[maybe_async_cfg::maybe(
-    sync(not(feature = "async")),
+    sync(feature = "sync"),
     async(feature = "async")
 )]
 impl<DI, IC, E> DateTimeAccess for Ds323x<DI, IC>
@@ -76,7 +74,7 @@ where
 }
 
 #[maybe_async_cfg::maybe(
-    sync(not(feature = "async")),
+    sync(feature = "sync"),
     async(feature = "async")
 )]
 impl<DI, IC, E> Rtcc for Ds323x<DI, IC>
@@ -222,7 +220,7 @@ where
         }
     }
 
-    async fn set_date(&mut self, date: &rtcc::NaiveDate) -> Result<(), Self::Error> {
+    async fn set_date(&mut self, date: &NaiveDate) -> Result<(), Self::Error> {
         if date.year() < 2000 || date.year() > 2100 {
             return Err(Error::InvalidInputData);
         }
@@ -239,7 +237,7 @@ where
 }
 
 #[maybe_async_cfg::maybe(
-    sync(not(feature = "async")),
+    sync(feature = "sync"),
     async(feature = "async")
 )]
 impl<DI, IC, E> Ds323x<DI, IC>
@@ -258,20 +256,6 @@ where
     }
 }
 
-fn hours_from_register(data: u8) -> Hours {
-    if is_24h_format(data) {
-        Hours::H24(packed_bcd_to_decimal(data & !BitFlags::H24_H12))
-    } else if is_am(data) {
-        Hours::AM(packed_bcd_to_decimal(
-            data & !(BitFlags::H24_H12 | BitFlags::AM_PM),
-        ))
-    } else {
-        Hours::PM(packed_bcd_to_decimal(
-            data & !(BitFlags::H24_H12 | BitFlags::AM_PM),
-        ))
-    }
-}
-
 fn year_from_registers(month: u8, year: u8) -> u16 {
     let century = month & BitFlags::CENTURY;
     let year = packed_bcd_to_decimal(year);
@@ -294,14 +278,6 @@ fn month_year_to_registers(month: u8, year: u16) -> (u8, u8) {
     }
 }
 
-fn is_24h_format(hours_data: u8) -> bool {
-    hours_data & BitFlags::H24_H12 == 0
-}
-
-fn is_am(hours_data: u8) -> bool {
-    hours_data & BitFlags::AM_PM == 0
-}
-
 fn get_h24(hour: Hours) -> u8 {
     match hour {
         Hours::H24(h) => h,
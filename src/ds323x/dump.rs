@@ -0,0 +1,183 @@
+//! Register dump and restore
+use crate::{BitFlags, Register};
+use core::fmt;
+
+// Number of registers read by a dump of each device
+pub(crate) const DS3231_DUMP_LEN: usize = 0x13;
+pub(crate) const DS3232_DUMP_LEN: usize = 0x14;
+pub(crate) const DS3234_DUMP_LEN: usize = 0x1A;
+
+/// Snapshot of the device registers
+///
+/// Contains the raw values of the timekeeping, alarm, control, status, aging
+/// offset and temperature registers as returned by `dump_registers()`. The
+/// configuration can be written back to a device with `restore_registers()`.
+///
+/// The `Debug` and `defmt::Format` implementations print the values in hexadecimal.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct RegisterDump {
+    /// Timekeeping registers (0x00-0x06)
+    pub datetime: [u8; 7],
+    /// Alarm1 registers (0x07-0x0A)
+    pub alarm1: [u8; 4],
+    /// Alarm2 registers (0x0B-0x0D)
+    pub alarm2: [u8; 3],
+    /// Control register (0x0E)
+    pub control: u8,
+    /// Status register (0x0F)
+    pub status: u8,
+    /// Aging offset register (0x10)
+    pub aging_offset: u8,
+    /// Temperature registers (0x11-0x12)
+    pub temperature: [u8; 2],
+    /// Temperature conversion register (0x13)
+    ///
+    /// Note: This is only available for DS3232 and DS3234 devices. It is
+    /// reserved on the DS3232.
+    pub temperature_conversion: Option<u8>,
+    /// SRAM address register (0x18)
+    ///
+    /// Note: This is only available for DS3234 devices.
+    pub sram_address: Option<u8>,
+    /// SRAM data register (0x19)
+    ///
+    /// Note: This is only available for DS3234 devices.
+    pub sram_data: Option<u8>,
+}
+
+impl RegisterDump {
+    // `data[0]` is the register address and the values start at `data[1]`.
+    pub(crate) fn from_payload(data: &[u8]) -> Self {
+        let registers = &data[1..];
+        let mut dump = RegisterDump {
+            datetime: [0; 7],
+            alarm1: [0; 4],
+            alarm2: [0; 3],
+            control: registers[usize::from(Register::CONTROL)],
+            status: registers[usize::from(Register::STATUS)],
+            aging_offset: registers[usize::from(Register::AGING_OFFSET)],
+            temperature: [0; 2],
+            temperature_conversion: registers.get(usize::from(Register::TEMP_CONV)).copied(),
+            sram_address: registers.get(usize::from(Register::SRAM_ADDRESS)).copied(),
+            sram_data: registers.get(usize::from(Register::SRAM_DATA)).copied(),
+        };
+        dump.datetime.copy_from_slice(&registers[0x00..0x07]);
+        dump.alarm1.copy_from_slice(&registers[0x07..0x0B]);
+        dump.alarm2.copy_from_slice(&registers[0x0B..0x0E]);
+        dump.temperature.copy_from_slice(&registers[0x11..0x13]);
+        dump
+    }
+
+    // Alarm, control, status and aging offset registers to write back,
+    // starting with the address of the first one.
+    //
+    // The oscillator stop flag is taken from `status` and the alarm matched
+    // flags are set so that they are not cleared.
+    pub(crate) fn configuration_payload(&self, status: u8) -> [u8; 11] {
+        const STATUS_CONFIGURATION: u8 =
+            BitFlags::BB32KHZ | BitFlags::CRATE1 | BitFlags::CRATE0 | BitFlags::EN32KHZ;
+        let status = (status & BitFlags::OSC_STOP) | (self.status & STATUS_CONFIGURATION);
+        let mut payload = [0; 11];
+        payload[0] = Register::ALARM1_SECONDS;
+        payload[1..5].copy_from_slice(&self.alarm1);
+        payload[5..8].copy_from_slice(&self.alarm2);
+        // do not start a conversion
+        payload[8] = self.control & !BitFlags::TEMP_CONV;
+        payload[9] = status | BitFlags::ALARM2F | BitFlags::ALARM1F;
+        payload[10] = self.aging_offset;
+        payload
+    }
+}
+
+impl fmt::Debug for RegisterDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegisterDump")
+            .field("datetime", &format_args!("{:02x?}", self.datetime))
+            .field("alarm1", &format_args!("{:02x?}", self.alarm1))
+            .field("alarm2", &format_args!("{:02x?}", self.alarm2))
+            .field("control", &format_args!("{:#010b}", self.control))
+            .field("status", &format_args!("{:#010b}", self.status))
+            .field("aging_offset", &format_args!("{:#04x}", self.aging_offset))
+            .field("temperature", &format_args!("{:02x?}", self.temperature))
+            .field(
+                "temperature_conversion",
+                &format_args!("{:#04x?}", self.temperature_conversion),
+            )
+            .field("sram_address", &format_args!("{:#04x?}", self.sram_address))
+            .field("sram_data", &format_args!("{:#04x?}", self.sram_data))
+            .finish()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for RegisterDump {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(
+            f,
+            "RegisterDump {{ datetime: {=[u8]:02x}, alarm1: {=[u8]:02x}, alarm2: {=[u8]:02x}, \
+             control: {=u8:#010b}, status: {=u8:#010b}, aging_offset: {=u8:#04x}, \
+             temperature: {=[u8]:02x}, temperature_conversion: {:#04x}, \
+             sram_address: {:#04x}, sram_data: {:#04x} }}",
+            self.datetime,
+            self.alarm1,
+            self.alarm2,
+            self.control,
+            self.status,
+            self.aging_offset,
+            self.temperature,
+            self.temperature_conversion,
+            self.sram_address,
+            self.sram_data,
+        )
+    }
+}
+
+maybe_async_cfg::content! {
+#![maybe_async_cfg::default(
+    idents(ReadData, WriteData, Ds323x),
+)]
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+use crate::{
+    interface::{ReadData, WriteData},
+    Ds323x,
+};
+use crate::Error;
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+impl<DI, IC, E> Ds323x<DI, IC>
+where
+    DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+{
+    // Reads the first `len` registers in one transaction.
+    pub(crate) async fn read_register_dump(
+        &mut self,
+        len: usize,
+    ) -> Result<RegisterDump, Error<E>> {
+        let mut data = [0; DS3234_DUMP_LEN + 1];
+        data[0] = Register::SECONDS;
+        let payload = &mut data[..=len];
+        self.iface.read_data(payload).await?;
+        Ok(RegisterDump::from_payload(payload))
+    }
+
+    // Writes back the alarm, control, status and aging offset registers in
+    // one transaction.
+    pub(crate) async fn write_register_dump(
+        &mut self,
+        dump: &RegisterDump,
+    ) -> Result<(), Error<E>> {
+        let mut payload = dump.configuration_payload(self.status);
+        self.iface.write_data(&mut payload).await?;
+        self.control = payload[8];
+        self.status = payload[9] & !(BitFlags::ALARM2F | BitFlags::ALARM1F);
+        Ok(())
+    }
+}
+}
@@ -1,22 +1,39 @@
 mod alarms;
 mod configuration;
+pub use self::configuration::{
+    Config, Control, Ds3234ExtendedConfig, ExtendedConfig, IntSqwOutput,
+};
+mod dump;
+pub use self::dump::RegisterDump;
+pub(crate) use self::dump::{DS3231_DUMP_LEN, DS3232_DUMP_LEN, DS3234_DUMP_LEN};
+mod occurrence;
+mod probe;
+pub use self::probe::Chip;
+#[cfg(feature = "async")]
+mod rtcc_async;
 #[cfg(feature = "async")]
-pub mod rtcc_async;
+pub use self::rtcc_async::{AsyncDateTimeAccess, AsyncRtcc};
+mod schedule;
 mod status;
+mod wait;
 
 pub use self::alarms::{
-    Alarm1Matching, Alarm2Matching, DayAlarm1, DayAlarm2, WeekdayAlarm1, WeekdayAlarm2,
+    Alarm1Config, Alarm1Matching, Alarm2Config, Alarm2Matching, DayAlarm1, DayAlarm2,
+    WeekdayAlarm1, WeekdayAlarm2,
 };
+pub use self::schedule::{Schedule, ScheduleError};
+pub use self::status::{AlarmFlags, Status};
+pub use self::wait::AlarmEvent;
 mod datetime;
-use crate::{BitFlags, Error, Hours, NaiveTime, Timelike};
+use crate::{BitFlags, Error, Hours};
 
 // Transforms a decimal number to packed BCD format
-fn decimal_to_packed_bcd(dec: u8) -> u8 {
+pub(crate) fn decimal_to_packed_bcd(dec: u8) -> u8 {
     ((dec / 10) << 4) | (dec % 10)
 }
 
 // Transforms a number in packed BCD format to decimal
-fn packed_bcd_to_decimal(bcd: u8) -> u8 {
+pub(crate) fn packed_bcd_to_decimal(bcd: u8) -> u8 {
     (bcd >> 4) * 10 + (bcd & 0xF)
 }
 
@@ -31,6 +48,37 @@ fn hours_to_register<E>(hours: Hours) -> Result<u8, Error<E>> {
     }
 }
 
+// Checks that an access of `len` bytes starting at `offset` fits in an SRAM of `capacity` bytes
+pub(crate) fn check_sram_range<E>(offset: u8, len: usize, capacity: usize) -> Result<(), Error<E>> {
+    if usize::from(offset) + len > capacity {
+        Err(Error::InvalidInputData)
+    } else {
+        Ok(())
+    }
+}
+
+fn hours_from_register(data: u8) -> Hours {
+    if is_24h_format(data) {
+        Hours::H24(packed_bcd_to_decimal(data & !BitFlags::H24_H12))
+    } else if is_am(data) {
+        Hours::AM(packed_bcd_to_decimal(
+            data & !(BitFlags::H24_H12 | BitFlags::AM_PM),
+        ))
+    } else {
+        Hours::PM(packed_bcd_to_decimal(
+            data & !(BitFlags::H24_H12 | BitFlags::AM_PM),
+        ))
+    }
+}
+
+fn is_24h_format(hours_data: u8) -> bool {
+    hours_data & BitFlags::H24_H12 == 0
+}
+
+fn is_am(hours_data: u8) -> bool {
+    hours_data & BitFlags::AM_PM == 0
+}
+
 fn some_or_invalid_error<T, E>(data: Option<T>) -> Result<T, Error<E>> {
     if let Some(data) = data {
         Ok(data)
@@ -59,6 +107,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn sram_range_within_capacity_is_valid() {
+        assert!(check_sram_range::<()>(0, 236, 236).is_ok());
+        assert!(check_sram_range::<()>(235, 1, 236).is_ok());
+        assert!(check_sram_range::<()>(255, 1, 256).is_ok());
+        assert!(check_sram_range::<()>(10, 0, 236).is_ok());
+    }
+
+    #[test]
+    fn sram_range_beyond_capacity_is_invalid() {
+        match check_sram_range::<()>(235, 2, 236) {
+            Err(Error::InvalidInputData) => (),
+            _ => panic!(),
+        }
+        match check_sram_range::<()>(0, 257, 256) {
+            Err(Error::InvalidInputData) => (),
+            _ => panic!(),
+        }
+    }
+
     #[test]
     fn can_convert_packed_bcd_to_decimal() {
         assert_eq!(0, packed_bcd_to_decimal(0b0000_0000));
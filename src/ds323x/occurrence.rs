@@ -0,0 +1,274 @@
+//! Computation of the next time an alarm matches
+
+use super::alarms::{Alarm1Config, Alarm1Matching, Alarm2Config, Alarm2Matching};
+use super::schedule::Schedule;
+use crate::{Hours, NaiveDateTime, Timelike};
+
+// The 12h hours are converted to 24h format as the device does:
+// 12 AM is midnight and 12 PM is noon.
+pub(super) fn hours_to_h24(hours: Hours) -> u8 {
+    match hours {
+        Hours::H24(h) => h,
+        Hours::AM(12) => 0,
+        Hours::AM(h) => h,
+        Hours::PM(12) => 12,
+        Hours::PM(h) => h + 12,
+    }
+}
+
+// The alarms number weekdays from Sunday = 1, as written by `set_datetime()`,
+// while schedules number them from Sunday = 0. Weekday 0 wraps around out of
+// range so that it never matches.
+fn weekday_from_sunday(weekday: u8) -> u8 {
+    weekday.wrapping_sub(1)
+}
+
+impl Schedule {
+    /// Schedule matching the same date and times as Alarm1.
+    pub(crate) fn from_alarm1(alarm: Alarm1Config) -> Self {
+        let (day, weekday, hour, minute, second, matching) = match alarm {
+            Alarm1Config::Day(a, m) => (Some(a.day), None, a.hour, a.minute, a.second, m),
+            Alarm1Config::Weekday(a, m) => (
+                None,
+                Some(weekday_from_sunday(a.weekday)),
+                a.hour,
+                a.minute,
+                a.second,
+                m,
+            ),
+        };
+        let hour = Some(hours_to_h24(hour));
+        match matching {
+            Alarm1Matching::OncePerSecond => Schedule::single(None, None, None, None, None),
+            Alarm1Matching::SecondsMatch => Schedule::single(None, None, None, None, Some(second)),
+            Alarm1Matching::MinutesAndSecondsMatch => {
+                Schedule::single(None, None, None, Some(minute), Some(second))
+            }
+            Alarm1Matching::HoursMinutesAndSecondsMatch => {
+                Schedule::single(None, None, hour, Some(minute), Some(second))
+            }
+            Alarm1Matching::AllMatch => {
+                Schedule::single(day, weekday, hour, Some(minute), Some(second))
+            }
+        }
+    }
+
+    /// Schedule matching the same date and times as Alarm2.
+    pub(crate) fn from_alarm2(alarm: Alarm2Config) -> Self {
+        let (day, weekday, hour, minute, matching) = match alarm {
+            Alarm2Config::Day(a, m) => (Some(a.day), None, a.hour, a.minute, m),
+            Alarm2Config::Weekday(a, m) => (
+                None,
+                Some(weekday_from_sunday(a.weekday)),
+                a.hour,
+                a.minute,
+                m,
+            ),
+        };
+        let hour = Some(hours_to_h24(hour));
+        // Alarm2 always fires at 00 seconds
+        match matching {
+            Alarm2Matching::OncePerMinute => Schedule::single(None, None, None, None, Some(0)),
+            Alarm2Matching::MinutesMatch => {
+                Schedule::single(None, None, None, Some(minute), Some(0))
+            }
+            Alarm2Matching::HoursAndMinutesMatch => {
+                Schedule::single(None, None, hour, Some(minute), Some(0))
+            }
+            Alarm2Matching::AllMatch => Schedule::single(day, weekday, hour, Some(minute), Some(0)),
+        }
+    }
+}
+
+/// Round up to the next whole minute unless already at 00 seconds.
+pub(crate) fn round_up_to_minute(datetime: NaiveDateTime) -> Option<NaiveDateTime> {
+    if datetime.second() == 0 && datetime.nanosecond() == 0 {
+        Some(datetime)
+    } else {
+        Schedule::new().next_after(datetime)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DayAlarm1, DayAlarm2, NaiveDate, WeekdayAlarm1, WeekdayAlarm2};
+
+    fn dt(y: i32, mo: u32, d: u32, h: u32, mi: u32, s: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, mo, d)
+            .unwrap()
+            .and_hms_opt(h, mi, s)
+            .unwrap()
+    }
+
+    fn day1(day: u8, hour: Hours, minute: u8, second: u8, m: Alarm1Matching) -> Schedule {
+        Schedule::from_alarm1(Alarm1Config::Day(
+            DayAlarm1 {
+                day,
+                hour,
+                minute,
+                second,
+            },
+            m,
+        ))
+    }
+
+    #[test]
+    fn converts_12h_hours() {
+        assert_eq!(0, hours_to_h24(Hours::AM(12)));
+        assert_eq!(1, hours_to_h24(Hours::AM(1)));
+        assert_eq!(12, hours_to_h24(Hours::PM(12)));
+        assert_eq!(23, hours_to_h24(Hours::PM(11)));
+        assert_eq!(7, hours_to_h24(Hours::H24(7)));
+    }
+
+    #[test]
+    fn once_per_second() {
+        let p = day1(1, Hours::H24(0), 0, 0, Alarm1Matching::OncePerSecond);
+        assert_eq!(
+            Some(dt(2020, 12, 31, 23, 59, 59)),
+            p.next_after(dt(2020, 12, 31, 23, 59, 58))
+        );
+        assert_eq!(
+            Some(dt(2021, 1, 1, 0, 0, 0)),
+            p.next_after(dt(2020, 12, 31, 23, 59, 59))
+        );
+    }
+
+    #[test]
+    fn seconds_match() {
+        let p = day1(1, Hours::H24(0), 0, 30, Alarm1Matching::SecondsMatch);
+        assert_eq!(
+            Some(dt(2020, 1, 1, 10, 5, 30)),
+            p.next_after(dt(2020, 1, 1, 10, 5, 10))
+        );
+        assert_eq!(
+            Some(dt(2020, 1, 1, 10, 6, 30)),
+            p.next_after(dt(2020, 1, 1, 10, 5, 30))
+        );
+    }
+
+    #[test]
+    fn hours_minutes_seconds_match_rolls_over_to_next_day() {
+        let p = day1(
+            1,
+            Hours::PM(12),
+            0,
+            0,
+            Alarm1Matching::HoursMinutesAndSecondsMatch,
+        );
+        assert_eq!(
+            Some(dt(2020, 1, 2, 12, 0, 0)),
+            p.next_after(dt(2020, 1, 1, 12, 0, 0))
+        );
+    }
+
+    #[test]
+    fn day_31_skips_short_months() {
+        let p = day1(31, Hours::H24(8), 0, 0, Alarm1Matching::AllMatch);
+        assert_eq!(
+            Some(dt(2021, 5, 31, 8, 0, 0)),
+            p.next_after(dt(2021, 3, 31, 9, 0, 0))
+        );
+    }
+
+    #[test]
+    fn day_29_skips_february_in_non_leap_years() {
+        let p = day1(29, Hours::H24(8), 0, 0, Alarm1Matching::AllMatch);
+        assert_eq!(
+            Some(dt(2021, 3, 29, 8, 0, 0)),
+            p.next_after(dt(2021, 1, 30, 0, 0, 0))
+        );
+        assert_eq!(
+            Some(dt(2024, 2, 29, 8, 0, 0)),
+            p.next_after(dt(2024, 1, 30, 0, 0, 0))
+        );
+    }
+
+    #[test]
+    fn weekday_alarm1() {
+        // 2020-05-01 is a Friday (6 counting from Sunday = 1)
+        let p = Schedule::from_alarm1(Alarm1Config::Weekday(
+            WeekdayAlarm1 {
+                weekday: 2,
+                hour: Hours::H24(7),
+                minute: 30,
+                second: 0,
+            },
+            Alarm1Matching::AllMatch,
+        ));
+        assert_eq!(
+            Some(dt(2020, 5, 4, 7, 30, 0)),
+            p.next_after(dt(2020, 5, 1, 19, 0, 0))
+        );
+    }
+
+    #[test]
+    fn alarm2_fires_at_zero_seconds() {
+        let p = Schedule::from_alarm2(Alarm2Config::Day(
+            DayAlarm2 {
+                day: 1,
+                hour: Hours::H24(0),
+                minute: 0,
+            },
+            Alarm2Matching::OncePerMinute,
+        ));
+        assert_eq!(
+            Some(dt(2020, 1, 1, 10, 6, 0)),
+            p.next_after(dt(2020, 1, 1, 10, 5, 0))
+        );
+    }
+
+    #[test]
+    fn weekday_alarm2_same_day_later() {
+        // 2020-05-01 is a Friday (6 counting from Sunday = 1)
+        let p = Schedule::from_alarm2(Alarm2Config::Weekday(
+            WeekdayAlarm2 {
+                weekday: 6,
+                hour: Hours::PM(8),
+                minute: 15,
+            },
+            Alarm2Matching::AllMatch,
+        ));
+        assert_eq!(
+            Some(dt(2020, 5, 1, 20, 15, 0)),
+            p.next_after(dt(2020, 5, 1, 19, 0, 0))
+        );
+    }
+
+    #[test]
+    fn rounds_up_to_next_minute() {
+        assert_eq!(
+            Some(dt(2020, 1, 1, 10, 5, 0)),
+            round_up_to_minute(dt(2020, 1, 1, 10, 5, 0))
+        );
+        assert_eq!(
+            Some(dt(2021, 1, 1, 0, 0, 0)),
+            round_up_to_minute(dt(2020, 12, 31, 23, 59, 1))
+        );
+        let with_nanos = dt(2020, 1, 1, 10, 5, 0).with_nanosecond(1).unwrap();
+        assert_eq!(
+            Some(dt(2020, 1, 1, 10, 6, 0)),
+            round_up_to_minute(with_nanos)
+        );
+    }
+
+    #[test]
+    fn impossible_pattern_never_matches() {
+        let p = day1(32, Hours::H24(0), 0, 0, Alarm1Matching::AllMatch);
+        assert_eq!(None, p.next_after(dt(2020, 1, 1, 0, 0, 0)));
+    }
+
+    #[test]
+    fn weekday_0_never_matches() {
+        let p = Schedule::from_alarm2(Alarm2Config::Weekday(
+            WeekdayAlarm2 {
+                weekday: 0,
+                hour: Hours::H24(0),
+                minute: 0,
+            },
+            Alarm2Matching::AllMatch,
+        ));
+        assert_eq!(None, p.next_after(dt(2020, 1, 1, 0, 0, 0)));
+    }
+}
@@ -0,0 +1,153 @@
+//! Device presence probe and chip identification
+use crate::{BitFlags, Register};
+
+// Number of registers read when probing (0x00-0x12)
+const PROBE_LEN: usize = 0x13;
+
+/// Chip identified by `probe()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Chip {
+    /// DS3231
+    DS3231,
+    /// DS3232
+    DS3232,
+    /// DS3234
+    DS3234,
+}
+
+// Checks the bits that always read as zero in the timekeeping and
+// temperature registers and that the day of the week is in range.
+// All bits of the control register are defined on every chip and the
+// status register bits 6-4 only read as zero on the DS3231, so they are
+// checked separately with `has_ds3232_status_bits()`.
+// `data[0]` is the register address and the values start at `data[1]`.
+fn reserved_bits_are_valid(data: &[u8]) -> bool {
+    let registers = &data[1..];
+    let dow = registers[usize::from(Register::DOW)];
+    registers[usize::from(Register::SECONDS)] & 0x80 == 0
+        && registers[usize::from(Register::MINUTES)] & 0x80 == 0
+        && registers[usize::from(Register::HOURS)] & 0x80 == 0
+        && (1..=7).contains(&dow)
+        && registers[usize::from(Register::DOM)] & 0xC0 == 0
+        && registers[usize::from(Register::MONTH)] & 0x60 == 0
+        && registers[usize::from(Register::TEMP_LSB)] & 0x3F == 0
+}
+
+// The status register bits 6-4 (BB32KHZ, CRATE1 and CRATE0) are reserved
+// and always read as zero on the DS3231.
+fn has_ds3232_status_bits(data: &[u8]) -> bool {
+    let status = data[1 + usize::from(Register::STATUS)];
+    status & (BitFlags::BB32KHZ | BitFlags::CRATE1 | BitFlags::CRATE0) != 0
+}
+
+maybe_async_cfg::content! {
+#![maybe_async_cfg::default(
+    idents(
+        I2cInterface, SpiInterface, ReadData, WriteData, Ds323x,
+        embedded_hal(sync, async = "embedded_hal_async"),
+    ),
+)]
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+use crate::{
+    interface::{I2cInterface, ReadData, SpiInterface, WriteData},
+    Ds323x,
+};
+use crate::Error;
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+impl<I2C, E, IC> Ds323x<I2cInterface<I2C>, IC>
+where
+    I2C: embedded_hal::i2c::I2c<Error = E>,
+    E: embedded_hal::i2c::Error,
+{
+    /// Probe whether a DS3231 or DS3232 device responds and identify it.
+    ///
+    /// The timekeeping, control, status and temperature registers are read
+    /// and the bits that always read as zero are checked. Returns `None` if
+    /// no device acknowledges its address or these bits do not match.
+    ///
+    /// The chip is then identified from the status register: it is a DS3232
+    /// if any of the bits 6-4 (BB32KHZ, CRATE1 and CRATE0) is set, since these
+    /// always read as zero on a DS3231. Otherwise the inverted value of the
+    /// first SRAM byte (register 0x14) is written, read back and the original
+    /// value restored. It is a DS3232 if the value could be written and a
+    /// DS3231 otherwise. This works independently of the IC marker of this
+    /// driver.
+    ///
+    /// Note: If the power is lost or the bus fails before the original value
+    /// is restored, the first SRAM byte of a DS3232 is left inverted. This
+    /// corrupts any data stored there, e.g. the header of an `SramRecord` at
+    /// offset 0. The SRAM is not written if the 32kHz output is enabled on
+    /// battery or the temperature conversion rate is not 64 seconds.
+    pub async fn probe(&mut self) -> Result<Option<Chip>, Error<E>> {
+        let mut data = [0; PROBE_LEN + 1];
+        data[0] = Register::SECONDS;
+        match self.iface.read_data(&mut data).await {
+            Ok(()) => (),
+            Err(Error::Comm(e)) if is_no_acknowledge(&e) => return Ok(None),
+            Err(e) => return Err(e),
+        }
+        if !reserved_bits_are_valid(&data) {
+            return Ok(None);
+        }
+        if has_ds3232_status_bits(&data) {
+            return Ok(Some(Chip::DS3232));
+        }
+        match self.sram_is_writable().await {
+            Ok(true) => Ok(Some(Chip::DS3232)),
+            Ok(false) => Ok(Some(Chip::DS3231)),
+            Err(Error::Comm(e)) if is_no_acknowledge(&e) => Ok(Some(Chip::DS3231)),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn sram_is_writable(&mut self) -> Result<bool, Error<E>> {
+        let original = self.iface.read_register(Register::SRAM_START).await?;
+        self.iface
+            .write_register(Register::SRAM_START, !original)
+            .await?;
+        let written = self.iface.read_register(Register::SRAM_START).await?;
+        self.iface
+            .write_register(Register::SRAM_START, original)
+            .await?;
+        Ok(written == !original)
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+impl<SPI, E, IC> Ds323x<SpiInterface<SPI>, IC>
+where
+    SPI: embedded_hal::spi::SpiDevice<u8, Error = E>,
+{
+    /// Probe whether a DS3234 device responds.
+    ///
+    /// The timekeeping, control, status and temperature registers are read
+    /// and the bits that always read as zero are checked. Returns `None` if
+    /// these bits do not match, e.g. when no device is connected.
+    pub async fn probe(&mut self) -> Result<Option<Chip>, Error<E>> {
+        let mut data = [0; PROBE_LEN + 1];
+        data[0] = Register::SECONDS;
+        self.iface.read_data(&mut data).await?;
+        if reserved_bits_are_valid(&data) {
+            Ok(Some(Chip::DS3234))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+fn is_no_acknowledge<E: embedded_hal_async::i2c::Error>(e: &E) -> bool {
+    matches!(e.kind(), embedded_hal_async::i2c::ErrorKind::NoAcknowledge(_))
+}
+}
@@ -1,7 +1,10 @@
-//! Data structures and traits to be implemented by real-time clock / calendar devices.
+//! Async counterparts of the `rtcc` traits to be implemented by real-time clock / calendar devices.
 //!
-//! Prefer to use only the methods from the `DateTimeAccess` rather than the individual
-//! methods from the `Rtcc` trait to avoid situations where the passing of time
+//! They mirror `rtcc::DateTimeAccess` and `rtcc::Rtcc` method for method and use the same
+//! `Hours` and `chrono` types.
+//!
+//! Prefer to use only the methods from the `AsyncDateTimeAccess` rather than the individual
+//! methods from the `AsyncRtcc` trait to avoid situations where the passing of time
 //! makes the results of the method calls inconsistent if you combine the results
 //! of several methods.
 //!
@@ -14,33 +17,14 @@
 //! 6. Your system thinks it is `01:00:00`.
 //!
 //! The same applies to the date as well, as well as when calling setter methods.
-//!
-//! # Features
-//!
-//! The optional `defmt` feature implements [defmt::Format] on some types.
-
-#![deny(unsafe_code, missing_docs)]
-#![no_std]
-
-pub use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
-
-/// Hours in either 12-hour (AM/PM) or 24-hour format
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
-pub enum Hours {
-    /// AM [1-12]
-    AM(u8),
-    /// PM [1-12]
-    PM(u8),
-    /// 24H format [0-23]
-    H24(u8),
-}
 
-/// Real-Time Clock / Calendar DateTimeAccess trait to read/write a complete
+use crate::{Hours, NaiveDate, NaiveDateTime, NaiveTime};
+
+/// Async Real-Time Clock / Calendar DateTimeAccess trait to read/write a complete
 /// date/time.
 ///
 /// Prefer to use only these methods rather than the individual methods from the
-/// `Rtcc` trait to avoid situations where the passing of time makes the results
+/// `AsyncRtcc` trait to avoid situations where the passing of time makes the results
 /// of the method calls inconsistent if you combine the results of several methods.
 ///
 /// For example, this can happen at certain timepoints:
@@ -52,7 +36,7 @@ pub enum Hours {
 /// 6. Your system thinks it is `01:00:00`.
 ///
 /// The same applies to the date as well, as well as when calling setter methods.
-pub trait DateTimeAccess {
+pub trait AsyncDateTimeAccess {
     /// Error type
     type Error;
 
@@ -66,11 +50,11 @@ pub trait DateTimeAccess {
     async fn set_datetime(&mut self, datetime: &NaiveDateTime) -> Result<(), Self::Error>;
 }
 
-/// Real-Time Clock / Calendar trait
+/// Async Real-Time Clock / Calendar trait
 ///
 /// If you want to combine calls to these methods, prefer to use only
-/// the `DateTimeAccess` trait to avoid situations where the passing of time makes the results
-/// of the method calls inconsistent.
+/// the `AsyncDateTimeAccess` trait to avoid situations where the passing of time makes
+/// the results of the method calls inconsistent.
 ///
 /// For example, this can happen at certain timepoints:
 /// 1. The time is `01:59:59`
@@ -81,7 +65,7 @@ pub trait DateTimeAccess {
 /// 6. Your system thinks it is `01:00:00`.
 ///
 /// The same applies to the date, as well as when calling setter methods.
-pub trait Rtcc: DateTimeAccess {
+pub trait AsyncRtcc: AsyncDateTimeAccess {
     /// Read the seconds.
     async fn seconds(&mut self) -> Result<u8, Self::Error>;
 
@@ -0,0 +1,628 @@
+//! Cron-like recurring schedules
+use super::occurrence::hours_to_h24;
+use crate::{
+    Alarm1Config, Alarm1Matching, Alarm2Config, Alarm2Matching, BitFlags, Datelike, DayAlarm1,
+    DayAlarm2, Error, Hours, NaiveDate, NaiveDateTime, NaiveTime, Timelike,
+};
+use core::str::FromStr;
+
+// Enough to find the 29th of February in any 8-year period without leap year
+const MAX_SEARCH_DAYS: u32 = 366 * 8;
+// Enough to find the previous 31st of a month
+const MAX_MONTH_SEARCH_DAYS: u32 = 62;
+
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+const MONTH_NAMES: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+/// Invalid schedule expression or field value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduleError;
+
+/// Cron-like recurring schedule
+///
+/// A schedule matches a date and time when the second, minute, hour and month
+/// match and either the day of the month or the weekday match, following the
+/// cron convention: if one of the day of the month or the weekday fields is
+/// unrestricted, only the other one is used.
+///
+/// It can be parsed from a cron expression with 5 fields
+/// (`minute hour day-of-month month weekday`, at 00 seconds) or 6 fields
+/// (`second minute hour day-of-month month weekday`). Each field is a
+/// comma-separated list of `*`, values `n`, ranges `a-b` and steps `*/s`,
+/// `a-b/s` or `a/s`. Months can also be given as `jan`-`dec` and weekdays as
+/// `sun`-`sat` or `0`-`7`, where both 0 and 7 are Sunday.
+///
+/// For example, every 15 minutes between 08:00 and 18:00 on weekdays:
+/// ```
+/// use ds323x::Schedule;
+///
+/// let schedule: Schedule = "*/15 8-17 * * mon-fri".parse().unwrap();
+/// let same = Schedule::new()
+///     .minutes((0..60).step_by(15))
+///     .and_then(|s| s.hours(8..=17))
+///     .and_then(|s| s.weekdays(1..=5))
+///     .unwrap();
+/// assert_eq!(schedule, same);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Schedule {
+    seconds: u64,
+    minutes: u64,
+    hours: u32,
+    days: u32,
+    months: u16,
+    weekdays: u8,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn mask<I: IntoIterator<Item = u8>>(values: I, min: u8, max: u8) -> Result<u64, ScheduleError> {
+    let mut mask = 0;
+    for value in values {
+        if value < min || value > max {
+            return Err(ScheduleError);
+        }
+        mask |= 1 << value;
+    }
+    if mask == 0 {
+        Err(ScheduleError)
+    } else {
+        Ok(mask)
+    }
+}
+
+fn range_mask(min: u8, max: u8) -> u64 {
+    ((1 << (max + 1)) - 1) & !((1 << min) - 1)
+}
+
+fn single_mask(value: Option<u8>, min: u8, max: u8) -> u64 {
+    match value {
+        Some(value) if value >= min && value <= max => 1 << value,
+        Some(_) => 0,
+        None => range_mask(min, max),
+    }
+}
+
+fn is_set(mask: u64, value: u32) -> bool {
+    mask & (1 << value) != 0
+}
+
+fn parse_value(value: &str, min: u8, max: u8, names: &[&str]) -> Result<u8, ScheduleError> {
+    let parsed = match names
+        .iter()
+        .position(|name| name.eq_ignore_ascii_case(value))
+    {
+        Some(index) => index as u8 + min,
+        None => value.parse().map_err(|_| ScheduleError)?,
+    };
+    if parsed < min || parsed > max {
+        Err(ScheduleError)
+    } else {
+        Ok(parsed)
+    }
+}
+
+fn parse_field(field: &str, min: u8, max: u8, names: &[&str]) -> Result<u64, ScheduleError> {
+    let mut mask = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, Some(step.parse::<u8>().map_err(|_| ScheduleError)?)),
+            None => (part, None),
+        };
+        let (first, last) = if range == "*" {
+            (min, max)
+        } else if let Some((first, last)) = range.split_once('-') {
+            (
+                parse_value(first, min, max, names)?,
+                parse_value(last, min, max, names)?,
+            )
+        } else {
+            let value = parse_value(range, min, max, names)?;
+            (value, if step.is_some() { max } else { value })
+        };
+        let step = step.unwrap_or(1);
+        if first > last || step == 0 {
+            return Err(ScheduleError);
+        }
+        mask |= mask_from((first..=last).step_by(usize::from(step)));
+    }
+    Ok(mask)
+}
+
+fn mask_from<I: IntoIterator<Item = u8>>(values: I) -> u64 {
+    values
+        .into_iter()
+        .fold(0, |mask, value| mask | (1 << value))
+}
+
+impl Schedule {
+    /// Create a schedule matching every minute at 00 seconds.
+    pub fn new() -> Self {
+        Schedule {
+            seconds: 1,
+            minutes: range_mask(0, 59),
+            hours: range_mask(0, 23) as u32,
+            days: range_mask(1, 31) as u32,
+            months: range_mask(1, 12) as u16,
+            weekdays: range_mask(0, 6) as u8,
+            any_day: true,
+            any_weekday: true,
+        }
+    }
+
+    /// Create a schedule matching a single value of each field given, or any
+    /// value of the fields that are `None`. Out of range values never match.
+    ///
+    /// Weekdays are numbered from Sunday = 0.
+    pub(crate) fn single(
+        day: Option<u8>,
+        weekday: Option<u8>,
+        hour: Option<u8>,
+        minute: Option<u8>,
+        second: Option<u8>,
+    ) -> Self {
+        Schedule {
+            seconds: single_mask(second, 0, 59),
+            minutes: single_mask(minute, 0, 59),
+            hours: single_mask(hour, 0, 23) as u32,
+            days: single_mask(day, 1, 31) as u32,
+            months: range_mask(1, 12) as u16,
+            weekdays: single_mask(weekday, 0, 6) as u8,
+            any_day: day.is_none(),
+            any_weekday: weekday.is_none(),
+        }
+    }
+
+    /// Set the matching seconds [0-59].
+    pub fn seconds<I: IntoIterator<Item = u8>>(mut self, values: I) -> Result<Self, ScheduleError> {
+        self.seconds = mask(values, 0, 59)?;
+        Ok(self)
+    }
+
+    /// Set the matching minutes [0-59].
+    pub fn minutes<I: IntoIterator<Item = u8>>(mut self, values: I) -> Result<Self, ScheduleError> {
+        self.minutes = mask(values, 0, 59)?;
+        Ok(self)
+    }
+
+    /// Set the matching hours [0-23].
+    pub fn hours<I: IntoIterator<Item = u8>>(mut self, values: I) -> Result<Self, ScheduleError> {
+        self.hours = mask(values, 0, 23)? as u32;
+        Ok(self)
+    }
+
+    /// Set the matching days of the month [1-31].
+    pub fn days<I: IntoIterator<Item = u8>>(mut self, values: I) -> Result<Self, ScheduleError> {
+        self.days = mask(values, 1, 31)? as u32;
+        self.any_day = false;
+        Ok(self)
+    }
+
+    /// Set the matching months [1-12].
+    pub fn months<I: IntoIterator<Item = u8>>(mut self, values: I) -> Result<Self, ScheduleError> {
+        self.months = mask(values, 1, 12)? as u16;
+        Ok(self)
+    }
+
+    /// Set the matching weekdays [0-7] where both 0 and 7 are Sunday.
+    pub fn weekdays<I: IntoIterator<Item = u8>>(
+        mut self,
+        values: I,
+    ) -> Result<Self, ScheduleError> {
+        self.weekdays = sunday_as_zero(mask(values, 0, 7)?);
+        self.any_weekday = false;
+        Ok(self)
+    }
+
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        let day = is_set(u64::from(self.days), date.day());
+        let weekday = is_set(
+            u64::from(self.weekdays),
+            date.weekday().num_days_from_sunday(),
+        );
+        let day_matches = match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        };
+        day_matches && is_set(u64::from(self.months), date.month())
+    }
+
+    /// First matching time of the day strictly after `after`, if any.
+    fn first_time(&self, after: Option<NaiveTime>) -> Option<NaiveTime> {
+        let after = after.map(|t| (t.hour(), t.minute(), t.second()));
+        for h in (0..24).filter(|h| is_set(u64::from(self.hours), *h)) {
+            if after.is_some_and(|a| h < a.0) {
+                continue;
+            }
+            for m in (0..60).filter(|m| is_set(self.minutes, *m)) {
+                if after.is_some_and(|a| (h, m) < (a.0, a.1)) {
+                    continue;
+                }
+                for s in (0..60).filter(|s| is_set(self.seconds, *s)) {
+                    if after.is_some_and(|a| (h, m, s) <= a) {
+                        continue;
+                    }
+                    return NaiveTime::from_hms_opt(h, m, s);
+                }
+            }
+        }
+        None
+    }
+
+    /// Whether a date and time matches the schedule.
+    pub(crate) fn matches(&self, datetime: NaiveDateTime) -> bool {
+        self.matches_date(datetime.date())
+            && is_set(u64::from(self.hours), datetime.hour())
+            && is_set(self.minutes, datetime.minute())
+            && is_set(self.seconds, datetime.second())
+    }
+
+    /// Next date and time strictly after `datetime` matching the schedule.
+    ///
+    /// Returns `None` if the schedule does not match in the following 8 years
+    /// (e.g. the 30th of February).
+    pub fn next_after(&self, datetime: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut date = datetime.date();
+        let mut after = Some(datetime.time());
+        for _ in 0..MAX_SEARCH_DAYS {
+            if self.matches_date(date) {
+                if let Some(time) = self.first_time(after) {
+                    return Some(date.and_time(time));
+                }
+            }
+            date = date.succ_opt()?;
+            after = None;
+        }
+        None
+    }
+}
+
+fn alarm_time(hour: Hours, minute: u8, second: u8) -> Option<NaiveTime> {
+    NaiveTime::from_hms_opt(
+        u32::from(hours_to_h24(hour)),
+        u32::from(minute),
+        u32::from(second),
+    )
+}
+
+/// Latest date and time not after `now` on a day of the month and at a time,
+/// that is, when an alarm for them last matched.
+fn last_on_day(day: u8, time: NaiveTime, now: NaiveDateTime) -> Option<NaiveDateTime> {
+    let mut date = now.date();
+    for _ in 0..MAX_MONTH_SEARCH_DAYS {
+        let datetime = date.and_time(time);
+        if date.day() == u32::from(day) && datetime <= now {
+            return Some(datetime);
+        }
+        date = date.pred_opt()?;
+    }
+    None
+}
+
+fn sunday_as_zero(mask: u64) -> u8 {
+    let sunday = if is_set(mask, 7) { 1 } else { 0 };
+    (mask as u8 & 0x7F) | sunday
+}
+
+impl FromStr for Schedule {
+    type Err = ScheduleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = [""; 6];
+        let mut count = 0;
+        for field in s.split_whitespace() {
+            *fields.get_mut(count).ok_or(ScheduleError)? = field;
+            count += 1;
+        }
+        let (seconds, rest) = match count {
+            5 => ("0", &fields[..5]),
+            6 => (fields[0], &fields[1..]),
+            _ => return Err(ScheduleError),
+        };
+        Ok(Schedule {
+            seconds: parse_field(seconds, 0, 59, &[])?,
+            minutes: parse_field(rest[0], 0, 59, &[])?,
+            hours: parse_field(rest[1], 0, 23, &[])? as u32,
+            days: parse_field(rest[2], 1, 31, &[])? as u32,
+            months: parse_field(rest[3], 1, 12, &MONTH_NAMES)? as u16,
+            weekdays: sunday_as_zero(parse_field(rest[4], 0, 7, &WEEKDAY_NAMES)?),
+            any_day: rest[2].starts_with('*'),
+            any_weekday: rest[4].starts_with('*'),
+        })
+    }
+}
+
+maybe_async_cfg::content! {
+#![maybe_async_cfg::default(
+    idents(
+        ReadData, WriteData, Ds323x,
+        DateTimeAccess(sync, async = "AsyncDateTimeAccess"),
+    ),
+)]
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+use crate::{
+    interface::{ReadData, WriteData},
+    DateTimeAccess, Ds323x,
+};
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+impl<DI, IC, E> Ds323x<DI, IC>
+where
+    DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+{
+    /// Set Alarm1 for the next occurrence of a schedule.
+    ///
+    /// Reads the current date and time and programs Alarm1 for the day of the
+    /// month and time of the next date and time matching the schedule, which
+    /// is returned.
+    ///
+    /// The alarm can match earlier than the returned date and time when the
+    /// same day of the month and time comes round first, e.g. for a yearly
+    /// schedule or one restricted to some months or weekdays. Once the alarm
+    /// has matched, call
+    /// [`service_alarm1_schedule()`](#method.service_alarm1_schedule), which
+    /// tells such matches apart from real occurrences and re-arms the alarm.
+    ///
+    /// Will return an `Error::InvalidInputData` if the schedule never matches.
+    /// The date and time is read again after programming the alarm. Will return
+    /// an `Error::AlarmMissed` if the occurrence has been reached in the meantime,
+    /// including when it is the current time.
+    pub async fn set_alarm1_schedule(
+        &mut self,
+        schedule: &Schedule,
+    ) -> Result<NaiveDateTime, Error<E>> {
+        let now = self.datetime().await?;
+        let when = schedule.next_after(now).ok_or(Error::InvalidInputData)?;
+        let alarm = DayAlarm1::at(when.day() as u8, when.time());
+        self.set_alarm1_day(alarm, Alarm1Matching::AllMatch).await?;
+        self.check_alarm_not_missed(when).await?;
+        Ok(when)
+    }
+
+    /// Set Alarm2 for the next occurrence of a schedule.
+    ///
+    /// Same as [`set_alarm1_schedule()`](#method.set_alarm1_schedule) for
+    /// Alarm2. Once the alarm has matched, call
+    /// [`service_alarm2_schedule()`](#method.service_alarm2_schedule).
+    ///
+    /// Will return an `Error::InvalidInputData` if the schedule never matches
+    /// or matches at other than 00 seconds, since Alarm2 always matches at 00 seconds.
+    pub async fn set_alarm2_schedule(
+        &mut self,
+        schedule: &Schedule,
+    ) -> Result<NaiveDateTime, Error<E>> {
+        if schedule.seconds != 1 {
+            return Err(Error::InvalidInputData);
+        }
+        let now = self.datetime().await?;
+        let when = schedule.next_after(now).ok_or(Error::InvalidInputData)?;
+        let alarm = DayAlarm2::at(when.day() as u8, when.time());
+        self.set_alarm2_day(alarm, Alarm2Matching::AllMatch).await?;
+        self.check_alarm_not_missed(when).await?;
+        Ok(when)
+    }
+
+    /// Handle a match of Alarm1 set with
+    /// [`set_alarm1_schedule()`](#method.set_alarm1_schedule).
+    ///
+    /// Nothing is done if the Alarm1 matched flag is not set. Otherwise, the
+    /// flag is cleared, Alarm1 is set for the next occurrence of the schedule
+    /// and the date and time at which the alarm matched is returned if it is
+    /// an occurrence of the schedule. `None` is returned if the alarm matched
+    /// on the same day of the month and time but in a month or on a weekday
+    /// that the schedule excludes.
+    ///
+    /// Will return an `Error::AlarmMissed` if the next occurrence has been
+    /// reached while setting the alarm.
+    pub async fn service_alarm1_schedule(
+        &mut self,
+        schedule: &Schedule,
+    ) -> Result<Option<NaiveDateTime>, Error<E>> {
+        if self.take_matched_flags(BitFlags::ALARM1F).await? == 0 {
+            return Ok(None);
+        }
+        let alarm = self.alarm1().await?;
+        let now = self.datetime().await?;
+        let matched = match alarm {
+            Alarm1Config::Day(a, Alarm1Matching::AllMatch) => {
+                alarm_time(a.hour, a.minute, a.second).and_then(|t| last_on_day(a.day, t, now))
+            }
+            _ => None,
+        };
+        self.set_alarm1_schedule(schedule).await?;
+        Ok(matched.filter(|m| schedule.matches(*m)))
+    }
+
+    /// Handle a match of Alarm2 set with
+    /// [`set_alarm2_schedule()`](#method.set_alarm2_schedule).
+    ///
+    /// Same as [`service_alarm1_schedule()`](#method.service_alarm1_schedule)
+    /// for Alarm2.
+    pub async fn service_alarm2_schedule(
+        &mut self,
+        schedule: &Schedule,
+    ) -> Result<Option<NaiveDateTime>, Error<E>> {
+        if self.take_matched_flags(BitFlags::ALARM2F).await? == 0 {
+            return Ok(None);
+        }
+        let alarm = self.alarm2().await?;
+        let now = self.datetime().await?;
+        let matched = match alarm {
+            Alarm2Config::Day(a, Alarm2Matching::AllMatch) => {
+                alarm_time(a.hour, a.minute, 0).and_then(|t| last_on_day(a.day, t, now))
+            }
+            _ => None,
+        };
+        self.set_alarm2_schedule(schedule).await?;
+        Ok(matched.filter(|m| schedule.matches(*m)))
+    }
+}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dt(y: i32, mo: u32, d: u32, h: u32, mi: u32, s: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, mo, d)
+            .unwrap()
+            .and_hms_opt(h, mi, s)
+            .unwrap()
+    }
+
+    fn parse(s: &str) -> Schedule {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn new_matches_every_minute() {
+        assert_eq!(parse("* * * * *"), Schedule::new());
+        assert_eq!(
+            Some(dt(2021, 1, 1, 0, 0, 0)),
+            Schedule::new().next_after(dt(2020, 12, 31, 23, 59, 0))
+        );
+    }
+
+    #[test]
+    fn every_15_minutes_on_weekday_working_hours() {
+        // 2021-04-02 is a Friday
+        let s = parse("*/15 8-17 * * mon-fri");
+        assert_eq!(
+            Some(dt(2021, 4, 2, 17, 45, 0)),
+            s.next_after(dt(2021, 4, 2, 17, 30, 0))
+        );
+        assert_eq!(
+            Some(dt(2021, 4, 5, 8, 0, 0)),
+            s.next_after(dt(2021, 4, 2, 17, 45, 0))
+        );
+    }
+
+    #[test]
+    fn with_seconds() {
+        let s = parse("10,40 * * * * *");
+        assert_eq!(
+            Some(dt(2021, 1, 1, 0, 0, 40)),
+            s.next_after(dt(2021, 1, 1, 0, 0, 10))
+        );
+        assert_eq!(
+            Some(dt(2021, 1, 1, 0, 1, 10)),
+            s.next_after(dt(2021, 1, 1, 0, 0, 40))
+        );
+    }
+
+    #[test]
+    fn day_of_month_or_weekday() {
+        // 2021-04-04 is a Sunday
+        let s = parse("0 12 15 * 7");
+        assert_eq!(
+            Some(dt(2021, 4, 4, 12, 0, 0)),
+            s.next_after(dt(2021, 4, 1, 0, 0, 0))
+        );
+        assert_eq!(
+            Some(dt(2021, 4, 15, 12, 0, 0)),
+            s.next_after(dt(2021, 4, 11, 12, 0, 0))
+        );
+    }
+
+    #[test]
+    fn leap_day() {
+        let s = parse("0 0 29 feb *");
+        assert_eq!(
+            Some(dt(2024, 2, 29, 0, 0, 0)),
+            s.next_after(dt(2021, 3, 1, 0, 0, 0))
+        );
+    }
+
+    #[test]
+    fn never_matches() {
+        assert_eq!(
+            None,
+            parse("0 0 30 2 *").next_after(dt(2021, 1, 1, 0, 0, 0))
+        );
+    }
+
+    #[test]
+    fn parses_ranges_and_steps() {
+        let s = parse("5/20 1-3,22 1-10/3 JAN-Mar 0,7");
+        assert_eq!(mask_from([5, 25, 45]), s.minutes);
+        assert_eq!(mask_from([1, 2, 3, 22]) as u32, s.hours);
+        assert_eq!(mask_from([1, 4, 7, 10]) as u32, s.days);
+        assert_eq!(mask_from([1, 2, 3]) as u16, s.months);
+        assert_eq!(1, s.weekdays);
+        assert!(!s.any_day);
+        assert!(!s.any_weekday);
+    }
+
+    #[test]
+    fn builder_matches_parser() {
+        let built = Schedule::new()
+            .seconds([30])
+            .and_then(|s| s.days([1, 15]))
+            .and_then(|s| s.weekdays([7]))
+            .unwrap();
+        assert_eq!(parse("30 * * 1,15 * 0"), built);
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        for expr in [
+            "",
+            "* * * *",
+            "* * * * * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "5-1 * * * *",
+            "*/0 * * * *",
+            "x * * * *",
+            "1, * * * *",
+        ] {
+            assert_eq!(Err(ScheduleError), expr.parse::<Schedule>(), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn builder_rejects_invalid_values() {
+        assert_eq!(Err(ScheduleError), Schedule::new().seconds([60]));
+        assert_eq!(Err(ScheduleError), Schedule::new().days([0]));
+        assert_eq!(Err(ScheduleError), Schedule::new().hours([]));
+    }
+
+    #[test]
+    fn matches_datetime() {
+        let s = parse("30 0 12 31 may *");
+        assert!(s.matches(dt(2021, 5, 31, 12, 0, 30)));
+        assert!(!s.matches(dt(2021, 3, 31, 12, 0, 30)));
+        assert!(!s.matches(dt(2021, 5, 31, 12, 0, 31)));
+    }
+
+    #[test]
+    fn finds_last_datetime_on_day() {
+        let noon = NaiveTime::from_hms_opt(12, 0, 0).unwrap();
+        assert_eq!(
+            Some(dt(2021, 3, 31, 12, 0, 0)),
+            last_on_day(31, noon, dt(2021, 3, 31, 12, 0, 5))
+        );
+        assert_eq!(
+            Some(dt(2021, 1, 31, 12, 0, 0)),
+            last_on_day(31, noon, dt(2021, 3, 31, 11, 59, 59))
+        );
+        assert_eq!(None, last_on_day(32, noon, dt(2021, 3, 31, 12, 0, 0)));
+    }
+}
@@ -5,16 +5,72 @@ maybe_async_cfg::content! {
 )]
 
 #[maybe_async_cfg::maybe(
-    sync(not(feature = "async")),
+    sync(feature = "sync"),
     async(feature = "async")
 )]
 use crate::{
     interface::{ReadData, WriteData},
-    BitFlags, Ds323x, Error, Register,
+    Ds323x,
 };
+use crate::{BitFlags, Error, Register, TempConvRate};
+
+/// Decoded status register
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Status {
+    /// The oscillator is or has been stopped (OSF)
+    pub has_been_stopped: bool,
+    /// The 32kHz output is enabled when battery-powered (BB32kHz)
+    ///
+    /// Note: This is only available for DS3232 and DS3234 devices.
+    pub enabled_32khz_output_on_battery: bool,
+    /// Temperature conversion rate (CRATE1 and CRATE0)
+    ///
+    /// Note: This is only available for DS3232 and DS3234 devices.
+    pub temperature_conversion_rate: TempConvRate,
+    /// The 32kHz output is enabled (EN32kHz)
+    pub enabled_32khz_output: bool,
+    /// A temperature conversion is in progress (BSY)
+    pub busy: bool,
+    /// Alarm2 has matched (A2F)
+    pub alarm2_matched: bool,
+    /// Alarm1 has matched (A1F)
+    pub alarm1_matched: bool,
+}
+
+impl Status {
+    pub(crate) fn from_register(status: u8) -> Self {
+        let temperature_conversion_rate =
+            match (status & BitFlags::CRATE1 != 0, status & BitFlags::CRATE0 != 0) {
+                (false, false) => TempConvRate::_64s,
+                (false, true) => TempConvRate::_128s,
+                (true, false) => TempConvRate::_256s,
+                (true, true) => TempConvRate::_512s,
+            };
+        Status {
+            has_been_stopped: (status & BitFlags::OSC_STOP) != 0,
+            enabled_32khz_output_on_battery: (status & BitFlags::BB32KHZ) != 0,
+            temperature_conversion_rate,
+            enabled_32khz_output: (status & BitFlags::EN32KHZ) != 0,
+            busy: (status & BitFlags::BUSY) != 0,
+            alarm2_matched: (status & BitFlags::ALARM2F) != 0,
+            alarm1_matched: (status & BitFlags::ALARM1F) != 0,
+        }
+    }
+}
+
+/// Alarm matched flags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AlarmFlags {
+    /// Alarm1 has matched
+    pub alarm1: bool,
+    /// Alarm2 has matched
+    pub alarm2: bool,
+}
 
 #[maybe_async_cfg::maybe(
-    sync(not(feature = "async")),
+    sync(feature = "sync"),
     async(feature = "async")
 )]
 impl<DI, IC, E> Ds323x<DI, IC>
@@ -27,6 +83,12 @@ where
         Ok((control & BitFlags::EOSC) == 0)
     }
 
+    /// Read and decode the whole status register at once.
+    pub async fn status(&mut self) -> Result<Status, Error<E>> {
+        let status = self.iface.read_register(Register::STATUS).await?;
+        Ok(Status::from_register(status))
+    }
+
     /// Read the busy status
     pub async fn busy(&mut self) -> Result<bool, Error<E>> {
         let status = self.iface.read_register(Register::STATUS).await?;
@@ -88,6 +150,35 @@ where
         self.iface.write_register(Register::STATUS, status).await
     }
 
+    /// Read and clear the alarm matched flags.
+    ///
+    /// The status register is read once and only the flags that were set are
+    /// cleared, so that an alarm matching in the meantime is not missed.
+    pub async fn take_alarm_flags(&mut self) -> Result<AlarmFlags, Error<E>> {
+        let matched = self
+            .take_matched_flags(BitFlags::ALARM1F | BitFlags::ALARM2F)
+            .await?;
+        Ok(AlarmFlags {
+            alarm1: (matched & BitFlags::ALARM1F) != 0,
+            alarm2: (matched & BitFlags::ALARM2F) != 0,
+        })
+    }
+
+    // Reads the status and clears the matched flags in `mask` that are set.
+    // Returns the flags that were cleared.
+    pub(crate) async fn take_matched_flags(&mut self, mask: u8) -> Result<u8, Error<E>> {
+        let status = self.iface.read_register(Register::STATUS).await?;
+        let matched = status & mask;
+        if matched != 0 {
+            // writing 1 to a flag keeps it unchanged
+            let keep = (BitFlags::ALARM1F | BitFlags::ALARM2F) & !matched;
+            self.iface
+                .write_register(Register::STATUS, self.status | keep)
+                .await?;
+        }
+        Ok(matched)
+    }
+
     /// Read the temperature.
     ///
     /// Note: It is possible to manually force a temperature conversion with
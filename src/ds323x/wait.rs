@@ -0,0 +1,147 @@
+//! Waiting for alarms to match
+use crate::{BitFlags, Error};
+#[cfg(feature = "sync")]
+use core::{convert::TryFrom, time::Duration};
+#[cfg(feature = "sync")]
+use embedded_hal::delay::DelayNs;
+
+/// Alarms that matched or to wait for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AlarmEvent {
+    /// Alarm1
+    Alarm1,
+    /// Alarm2
+    Alarm2,
+    /// Both Alarm1 and Alarm2
+    Both,
+}
+
+impl AlarmEvent {
+    fn flags(self) -> u8 {
+        match self {
+            AlarmEvent::Alarm1 => BitFlags::ALARM1F,
+            AlarmEvent::Alarm2 => BitFlags::ALARM2F,
+            AlarmEvent::Both => BitFlags::ALARM1F | BitFlags::ALARM2F,
+        }
+    }
+
+    fn from_flags(flags: u8) -> Option<Self> {
+        match (
+            flags & BitFlags::ALARM1F != 0,
+            flags & BitFlags::ALARM2F != 0,
+        ) {
+            (true, true) => Some(AlarmEvent::Both),
+            (true, false) => Some(AlarmEvent::Alarm1),
+            (false, true) => Some(AlarmEvent::Alarm2),
+            (false, false) => None,
+        }
+    }
+}
+
+maybe_async_cfg::content! {
+#![maybe_async_cfg::default(
+    idents(ReadData, WriteData, Ds323x),
+)]
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+use crate::{
+    interface::{ReadData, WriteData},
+    Ds323x,
+};
+#[cfg(feature = "async")]
+use embedded_hal::digital::Error as _;
+#[cfg(feature = "async")]
+use embedded_hal_async::digital::Wait;
+
+#[maybe_async_cfg::maybe(async(feature = "async"))]
+impl<DI, IC, E> Ds323x<DI, IC>
+where
+    DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+{
+    /// Wait until any of the given alarms matches.
+    ///
+    /// `pin` is the input connected to the INT/SQW output. The INT/SQW output
+    /// is set to be used as interrupt output, the interrupts of the given
+    /// alarms are enabled and the interrupt of the other alarm is disabled.
+    /// Then this waits for the pin to go low (falling edge) and returns which
+    /// of the given alarms matched, clearing their matched flags. Returns
+    /// immediately if any of them had already matched.
+    ///
+    /// Will return an `Error::Pin` with the kind of the pin error if waiting
+    /// on the pin fails.
+    pub async fn wait_for_alarm<P: Wait>(
+        &mut self,
+        pin: &mut P,
+        alarms: AlarmEvent,
+    ) -> Result<AlarmEvent, Error<E>> {
+        self.use_int_sqw_output_as_interrupt().await?;
+        // Otherwise the pin would be held low while the other alarm has matched
+        match alarms {
+            AlarmEvent::Alarm1 => self.disable_alarm2_interrupts().await?,
+            AlarmEvent::Alarm2 => self.disable_alarm1_interrupts().await?,
+            AlarmEvent::Both => (),
+        }
+        if alarms != AlarmEvent::Alarm2 {
+            self.enable_alarm1_interrupts().await?;
+        }
+        if alarms != AlarmEvent::Alarm1 {
+            self.enable_alarm2_interrupts().await?;
+        }
+        loop {
+            pin.wait_for_low().await.map_err(|e| Error::Pin(e.kind()))?;
+            let matched = self.take_matched_flags(alarms.flags()).await?;
+            if let Some(event) = AlarmEvent::from_flags(matched) {
+                return Ok(event);
+            }
+        }
+    }
+}
+
+#[maybe_async_cfg::maybe(sync(feature = "sync"))]
+impl<DI, IC, E> Ds323x<DI, IC>
+where
+    DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+{
+    /// Wait until Alarm1 or Alarm2 matches by polling their matched flags.
+    ///
+    /// The flags are read every `poll_interval` until any of them is set or
+    /// `timeout` has elapsed. Returns which alarms matched and clears their
+    /// matched flags without clearing the flag of the other alarm.
+    ///
+    /// The delays are done in whole microseconds. A `poll_interval` shorter
+    /// than 1µs is raised to 1µs.
+    ///
+    /// Will return an `Error::Timeout` if no alarm matched within `timeout`.
+    pub fn wait_for_alarm<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<AlarmEvent, Error<E>> {
+        let poll_interval_us = poll_interval.as_micros().max(1);
+        let timeout_us = timeout.as_micros();
+        let mut elapsed_us = 0;
+        loop {
+            let matched = self.take_matched_flags(AlarmEvent::Both.flags())?;
+            if let Some(event) = AlarmEvent::from_flags(matched) {
+                return Ok(event);
+            }
+            if elapsed_us >= timeout_us {
+                return Err(Error::Timeout);
+            }
+            // `delay_us()` takes at most `u32::MAX` microseconds at once
+            let mut remaining_us = poll_interval_us;
+            while remaining_us > 0 {
+                let us = u32::try_from(remaining_us).unwrap_or(u32::MAX);
+                delay.delay_us(us);
+                remaining_us -= u128::from(us);
+            }
+            elapsed_us += poll_interval_us;
+        }
+    }
+}
+}
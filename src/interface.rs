@@ -1,19 +1,97 @@
 //! I2C/SPI interfaces
+//!
+//! The driver accesses the device registers through the [`ReadData`] and
+//! [`WriteData`] traits. [`I2cInterface`] and [`SpiInterface`] are the
+//! implementations used by `new_ds3231()`, `new_ds3232()` and `new_ds3234()`.
+//!
+//! A custom interface, for example an I2C mux channel wrapper, a bridge to
+//! remote registers or a test double, can be used by implementing both traits
+//! and creating the driver with `new_ds3231_with_interface()`,
+//! `new_ds3232_with_interface()` or `new_ds3234_with_interface()`. The
+//! interface is returned by `destroy_interface()`.
+//!
+//! The implementations must follow these rules:
+//! - `read_register()` and `write_register()` access a single register.
+//! - In `read_data()` and `write_data()`, the first element of the payload is
+//!   the address of the first register and the rest of the elements are the
+//!   values of that and the following registers. `read_data()` stores the
+//!   values read in the payload after the address.
+//! - The payload may be modified, e.g. `SpiInterface` sets the write bit of
+//!   the address.
+//! - The error type must be `Error<E>`, where transport errors are reported
+//!   as `Error::Comm(E)`.
+//!
+//! The async driver uses the traits of the same name in the `asynch` module.
+//!
+//! ```
+//! use ds323x::{
+//!     interface::{ReadData, WriteData},
+//!     DateTimeAccess, Ds323x, Error, NaiveDate,
+//! };
+//!
+//! /// Registers kept in memory
+//! struct Registers([u8; 0x13]);
+//!
+//! impl ReadData for Registers {
+//!     type Error = Error<()>;
+//!
+//!     fn read_register(&mut self, register: u8) -> Result<u8, Self::Error> {
+//!         Ok(self.0[usize::from(register)])
+//!     }
+//!
+//!     fn read_data(&mut self, payload: &mut [u8]) -> Result<(), Self::Error> {
+//!         let start = usize::from(payload[0]);
+//!         let end = start + payload.len() - 1;
+//!         payload[1..].copy_from_slice(&self.0[start..end]);
+//!         Ok(())
+//!     }
+//! }
+//!
+//! impl WriteData for Registers {
+//!     type Error = Error<()>;
+//!
+//!     fn write_register(&mut self, register: u8, data: u8) -> Result<(), Self::Error> {
+//!         self.0[usize::from(register)] = data;
+//!         Ok(())
+//!     }
+//!
+//!     fn write_data(&mut self, payload: &mut [u8]) -> Result<(), Self::Error> {
+//!         let start = usize::from(payload[0]);
+//!         let end = start + payload.len() - 1;
+//!         self.0[start..end].copy_from_slice(&payload[1..]);
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let mut rtc = Ds323x::new_ds3231_with_interface(Registers([0; 0x13]));
+//! let datetime = NaiveDate::from_ymd_opt(2020, 5, 1)
+//!     .unwrap()
+//!     .and_hms_opt(19, 59, 58)
+//!     .unwrap();
+//! rtc.set_datetime(&datetime).unwrap();
+//! assert_eq!(datetime, rtc.datetime().unwrap());
+//!
+//! let registers = rtc.destroy_interface();
+//! assert_eq!(0x58, registers.0[0]);
+//! ```
 maybe_async_cfg::content! {
 #![maybe_async_cfg::default(
-    idents(ReadData, WriteData, Ds323x),
+    idents(
+        ReadData, WriteData, Ds323x,
+        embedded_hal(sync, async = "embedded_hal_async"),
+    ),
 )]
 
-use crate::{private, Error, DEVICE_ADDRESS};
-
-#[cfg(not(feature = "async"))]
-use embedded_hal::{i2c, spi};
-#[cfg(feature = "async")]
-use embedded_hal_async::{i2c, spi};
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+use crate::Ds323x;
+use crate::{Error, DEVICE_ADDRESS};
 
 /// I2C interface
 #[maybe_async_cfg::maybe(
-    sync(not(feature = "async")),
+    sync(feature = "sync"),
     async(feature = "async")
 )]
 #[derive(Debug, Default)]
@@ -24,7 +102,7 @@ pub struct I2cInterface<I2C> {
 
 /// SPI interface
 #[maybe_async_cfg::maybe(
-    sync(not(feature = "async")),
+    sync(feature = "sync"),
     async(feature = "async")
 )]
 #[derive(Debug, Default)]
@@ -33,12 +111,25 @@ pub struct SpiInterface<SPI> {
     pub(crate) spi: SPI,
 }
 
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+impl<DI, IC> Ds323x<DI, IC> {
+    /// Destroy driver instance, return the interface.
+    pub fn destroy_interface(self) -> DI {
+        self.iface
+    }
+}
+
 /// Write data
+///
+/// See the [module documentation](self) for how to implement it.
 #[maybe_async_cfg::maybe(
-    sync(not(feature = "async")),
+    sync(feature = "sync"),
     async(feature = "async")
 )]
-pub trait WriteData: private::Sealed {
+pub trait WriteData {
     /// Error type
     type Error;
     /// Write to an u8 register
@@ -48,12 +139,12 @@ pub trait WriteData: private::Sealed {
 }
 
 #[maybe_async_cfg::maybe(
-    sync(not(feature = "async")),
+    sync(feature = "sync"),
     async(feature = "async")
 )]
 impl<I2C, E> WriteData for I2cInterface<I2C>
 where
-    I2C: i2c::I2c<Error = E>,
+    I2C: embedded_hal::i2c::I2c<Error = E>,
 {
     type Error = Error<E>;
     async fn write_register(&mut self, register: u8, data: u8) -> Result<(), Self::Error> {
@@ -73,12 +164,12 @@ where
 }
 
 #[maybe_async_cfg::maybe(
-    sync(not(feature = "async")),
+    sync(feature = "sync"),
     async(feature = "async")
 )]
 impl<SPI, E> WriteData for SpiInterface<SPI>
 where
-    SPI: spi::SpiDevice<u8, Error = E>,
+    SPI: embedded_hal::spi::SpiDevice<u8, Error = E>,
 {
     type Error = Error<E>;
     async fn write_register(&mut self, register: u8, data: u8) -> Result<(), Self::Error> {
@@ -93,11 +184,13 @@ where
 }
 
 /// Read data
+///
+/// See the [module documentation](self) for how to implement it.
 #[maybe_async_cfg::maybe(
-    sync(not(feature = "async")),
+    sync(feature = "sync"),
     async(feature = "async")
 )]
-pub trait ReadData: private::Sealed {
+pub trait ReadData {
     /// Error type
     type Error;
     /// Read an u8 register
@@ -107,12 +200,12 @@ pub trait ReadData: private::Sealed {
 }
 
 #[maybe_async_cfg::maybe(
-    sync(not(feature = "async")),
+    sync(feature = "sync"),
     async(feature = "async")
 )]
 impl<I2C, E> ReadData for I2cInterface<I2C>
 where
-    I2C: i2c::I2c<Error = E>,
+    I2C: embedded_hal::i2c::I2c<Error = E>,
 {
     type Error = Error<E>;
     async fn read_register(&mut self, register: u8) -> Result<u8, Self::Error> {
@@ -134,12 +227,12 @@ where
 }
 
 #[maybe_async_cfg::maybe(
-    sync(not(feature = "async")),
+    sync(feature = "sync"),
     async(feature = "async")
 )]
 impl<SPI, E> ReadData for SpiInterface<SPI>
 where
-    SPI: spi::SpiDevice<u8, Error = E>,
+    SPI: embedded_hal::spi::SpiDevice<u8, Error = E>,
 {
     type Error = Error<E>;
     async fn read_register(&mut self, register: u8) -> Result<u8, Self::Error> {
@@ -160,3 +253,9 @@ where
     }
 }
 }
+
+#[cfg(feature = "sync")]
+pub use self::{
+    I2cInterfaceSync as I2cInterface, ReadDataSync as ReadData, SpiInterfaceSync as SpiInterface,
+    WriteDataSync as WriteData,
+};
@@ -7,18 +7,36 @@
 //! This driver allows you to:
 //! - Read and set date and time in 12-hour and 24-hour format. See: [`datetime`].
 //! - Read and set date and time individual elements. For example, see: [`year`].
+//! - Access the date and time through the `rtcc` traits or, with the `async` feature,
+//!   their async counterparts `AsyncDateTimeAccess` and `AsyncRtcc`.
 //! - Enable and disable the real-time clock. See: [`enable`].
 //! - Read the busy status. See [`busy`].
+//! - Read the whole decoded status at once. See [`status`].
 //! - Read whether the oscillator is or has been stopped. See [`has_been_stopped`].
 //! - Clear the has-been-stopped flag. See [`clear_has_been_stopped_flag`].
 //! - Set and read the aging offset. See [`set_aging_offset`].
 //! - Select the function of the INT/SQW output pin. See [`use_int_sqw_output_as_interrupt`].
+//! - Read and write the whole decoded control register at once. See [`control`].
+//! - Resynchronize the cached configuration with the device. See [`sync_from_device`].
+//! - Apply and read back the whole configuration at once. See [`Config`].
+//! - Dump all registers and restore the configuration from a dump. See [`RegisterDump`].
+//! - Probe whether a device responds and identify the chip. See `probe`.
 //! - Alarms:
 //!     - Set alarms 1 and 2 with several matching policies. See [`set_alarm1_day`].
 //!     - Set alarms 1 and 2 for a time. See [`set_alarm1_hms`].
+//!     - Set alarms 1 and 2 for a date and time. See [`set_alarm1_at`].
+//!     - Set alarms 1 and 2 to fire after an interval. See [`set_alarm1_in`].
+//!     - Multiplex several virtual alarms on Alarm1. See [`AlarmScheduler`].
+//!     - Set alarms 1 and 2 for the next occurrence of a cron-like [`Schedule`]. See [`set_alarm1_schedule`]
+//!       and [`service_alarm1_schedule`] to re-arm them after each match.
+//!     - Read back the configuration of alarms 1 and 2. See [`alarm1`].
+//!     - Compute when alarms 1 or 2 will next match. See [`next_alarm1_occurrence`].
 //!     - Read whether alarms 1 or 2 have matched. See [`has_alarm1_matched`].
 //!     - Clear flag indicating that alarms 1 or 2 have matched. See [`clear_alarm1_matched_flag`].
+//!     - Read and clear both alarm matched flags at once. See [`take_alarm_flags`].
 //!     - Enable and disable alarms 1 and 2 interrupt generation. See [`enable_alarm1_interrupts`].
+//!     - Wait for alarms 1 or 2 to match by polling or, with the `async` feature,
+//!       on the INT/SQW pin. See `wait_for_alarm`.
 //! - Wave generation:
 //!     - Enable and disable the square-wave generation. See [`enable_square_wave`].
 //!     - Select the square-wave frequency. See [`set_square_wave_frequency`].
@@ -29,6 +47,16 @@
 //!     - Force a temperature conversion and time compensation. See [`convert_temperature`].
 //!     - Set the temperature conversion rate. See [`set_temperature_conversion_rate`].
 //!     - Enable and disable the temperature conversions when battery-powered. See [`enable_temperature_conversions_on_battery`].
+//! - Read and write the battery-backed SRAM (DS3232 and DS3234). See [`read_sram`].
+//! - Write storage code once for the DS3232 and DS3234 through the [`Sram`] trait.
+//! - Use the SRAM through the `embedded-storage` traits with `SramStorage` (`storage` feature).
+//! - Store a versioned, CRC-checked record in the SRAM. See [`SramRecord`].
+//! - Use a custom register interface, e.g. an I2C mux channel or a test double.
+//!   See [`interface`].
+//! - Test drivers and application code against a simulated device (`sim` feature).
+//!   See `sim::Simulator`.
+//! - Use the blocking (`sync` feature) and async (`async` feature) drivers side by side
+//!   in the same binary. See [`blocking`] and `asynch`.
 //!
 //! [`datetime`]: Ds323x::datetime
 //! [`year`]: Ds323x::year
@@ -36,21 +64,32 @@
 //! [`temperature`]: Ds323x::temperature
 //! [`convert_temperature`]: Ds323x::convert_temperature
 //! [`busy`]: Ds323x::busy
+//! [`status`]: Ds323x::status
 //! [`has_been_stopped`]: Ds323x::has_been_stopped
 //! [`clear_has_been_stopped_flag`]: Ds323x::clear_has_been_stopped_flag
 //! [`set_aging_offset`]: Ds323x::set_aging_offset
 //! [`enable_32khz_output`]: Ds323x::enable_32khz_output
 //! [`use_int_sqw_output_as_interrupt`]: Ds323x::use_int_sqw_output_as_interrupt
+//! [`control`]: Ds323x::control
+//! [`sync_from_device`]: Ds323x::sync_from_device
 //! [`enable_square_wave`]: Ds323x::enable_square_wave
 //! [`set_square_wave_frequency`]: Ds323x::set_square_wave_frequency
 //! [`set_alarm1_day`]: Ds323x::set_alarm1_day
 //! [`set_alarm1_hms`]: Ds323x::set_alarm1_hms
+//! [`set_alarm1_at`]: Ds323x::set_alarm1_at
+//! [`set_alarm1_in`]: Ds323x::set_alarm1_in
+//! [`set_alarm1_schedule`]: Ds323x::set_alarm1_schedule
+//! [`service_alarm1_schedule`]: Ds323x::service_alarm1_schedule
+//! [`alarm1`]: Ds323x::alarm1
+//! [`next_alarm1_occurrence`]: Ds323x::next_alarm1_occurrence
 //! [`has_alarm1_matched`]: Ds323x::has_alarm1_matched
 //! [`clear_alarm1_matched_flag`]: Ds323x::clear_alarm1_matched_flag
+//! [`take_alarm_flags`]: Ds323x::take_alarm_flags
 //! [`enable_alarm1_interrupts`]: Ds323x::enable_alarm1_interrupts
 //! [`enable_32khz_output_on_battery`]: Ds323x::enable_32khz_output_on_battery
 //! [`set_temperature_conversion_rate`]: Ds323x::set_temperature_conversion_rate
 //! [`enable_temperature_conversions_on_battery`]: Ds323x::enable_temperature_conversions_on_battery
+//! [`read_sram`]: Ds323x::read_sram
 //!
 //! ## The devices
 //!
@@ -361,6 +400,21 @@
 //! let time = NaiveTime::from_hms(19, 59, 58);
 //! rtc.set_alarm1_hms(time).unwrap();
 //! ```
+//!
+//! ### Store some data in the battery-backed SRAM
+//!
+//! This is only available for the devices DS3232 and DS3234.
+//!
+//! ```no_run
+//! use ds323x::Ds323x;
+//! use linux_embedded_hal::I2cdev;
+//!
+//! let dev = I2cdev::new("/dev/i2c-1").unwrap();
+//! let mut rtc = Ds323x::new_ds3232(dev);
+//! rtc.write_sram(0, &[0xAB, 0xCD]).unwrap();
+//! let mut data = [0; 2];
+//! rtc.read_sram(0, &mut data).unwrap();
+//! ```
 
 #![deny(unsafe_code, missing_docs)]
 //TODO: Get rid of async functions in interface traits. Return back this lib to its previous sync implementation.
@@ -368,16 +422,10 @@
 #![no_std]
 
 use core::marker::PhantomData;
-#[cfg(not(feature = "async"))]
-use embedded_hal::spi::{Mode, MODE_1, MODE_3};
-#[cfg(feature = "async")]
 use embedded_hal_async::spi::{Mode, MODE_1, MODE_3};
 
 #[cfg(feature = "async")]
-pub use crate::ds323x::rtcc_async::{
-    DateTimeAccess, Datelike, Hours, NaiveDate, NaiveDateTime, NaiveTime, Rtcc, Timelike,
-};
-#[cfg(not(feature = "async"))]
+pub use crate::ds323x::{AsyncDateTimeAccess, AsyncRtcc};
 pub use rtcc::{
     DateTimeAccess, Datelike, Hours, NaiveDate, NaiveDateTime, NaiveTime, Rtcc, Timelike,
 };
@@ -388,8 +436,11 @@ pub const SPI_MODE_1: Mode = MODE_1;
 pub const SPI_MODE_3: Mode = MODE_3;
 
 /// All possible errors in this crate
+///
+/// New variants may be added without a breaking change.
 #[derive(Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
 pub enum Error<E> {
     /// I²C/SPI bus error
     Comm(E),
@@ -400,6 +451,22 @@ pub enum Error<E> {
     /// It was not possible to read a valid date and/or time.
     /// The device is probably missing initialization.
     InvalidDeviceState,
+    /// No record was found in the SRAM.
+    ///
+    /// The SRAM contents were lost or a record was never stored.
+    RecordNotFound,
+    /// The record stored in the SRAM is corrupted.
+    RecordCorrupted,
+    /// The record stored in the SRAM is valid but has a different version.
+    RecordVersionMismatch,
+    /// The alarm date and time was reached while the alarm was being set.
+    AlarmMissed,
+    /// All virtual alarms of the scheduler are in use.
+    SchedulerFull,
+    /// Interrupt pin error
+    Pin(embedded_hal::digital::ErrorKind),
+    /// Timed out waiting for an alarm
+    Timeout,
 }
 
 /// Square-wave output frequency
@@ -448,7 +515,11 @@ impl Register {
     const STATUS: u8 = 0x0F;
     const AGING_OFFSET: u8 = 0x10;
     const TEMP_MSB: u8 = 0x11;
+    const TEMP_LSB: u8 = 0x12;
     const TEMP_CONV: u8 = 0x13;
+    const SRAM_START: u8 = 0x14;
+    const SRAM_ADDRESS: u8 = 0x18;
+    const SRAM_DATA: u8 = 0x19;
 }
 
 struct BitFlags;
@@ -480,6 +551,8 @@ impl BitFlags {
 
 const DEVICE_ADDRESS: u8 = 0b110_1000;
 const CONTROL_POR_VALUE: u8 = 0b0001_1100;
+/// Maximum number of SRAM bytes transferred in a single bus transaction
+const SRAM_CHUNK_SIZE: usize = 32;
 
 /// IC markers
 pub mod ic {
@@ -492,7 +565,7 @@ pub mod ic {
 }
 
 /// DS3231, DS3232 and DS3234 RTC driver
-#[maybe_async_cfg::maybe(sync(not(feature = "async")), async(feature = "async"))]
+#[maybe_async_cfg::maybe(sync(feature = "sync"), async(feature = "async"))]
 #[derive(Debug, Default)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Ds323x<DI, IC> {
@@ -505,22 +578,69 @@ pub struct Ds323x<DI, IC> {
 mod ds323x;
 pub mod interface;
 pub use crate::ds323x::{
-    Alarm1Matching, Alarm2Matching, DayAlarm1, DayAlarm2, WeekdayAlarm1, WeekdayAlarm2,
+    Alarm1Config, Alarm1Matching, Alarm2Config, Alarm2Matching, AlarmEvent, AlarmFlags, Chip,
+    Config, Control, DayAlarm1, DayAlarm2, Ds3234ExtendedConfig, ExtendedConfig, IntSqwOutput,
+    RegisterDump, Schedule, ScheduleError, Status, WeekdayAlarm1, WeekdayAlarm2,
 };
 mod ds3231;
 mod ds3232;
 mod ds3234;
+mod record;
+#[maybe_async_cfg::maybe(idents(SramRecord), sync(feature = "sync"), async(feature = "async"))]
+pub use crate::record::SramRecord;
+pub use crate::record::RECORD_HEADER_SIZE;
+mod scheduler;
+pub use crate::scheduler::{AlarmScheduler, FiredAlarms};
+mod sram;
+#[maybe_async_cfg::maybe(idents(Sram), sync(feature = "sync"), async(feature = "async"))]
+pub use crate::sram::Sram;
+#[cfg(feature = "storage")]
+mod storage;
+#[cfg(feature = "storage")]
+#[maybe_async_cfg::maybe(
+    idents(SramStorage),
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+pub use crate::storage::SramStorage;
+#[cfg(feature = "sim")]
+pub mod sim;
 
-mod private {
-    use super::{ic, interface};
-    pub trait Sealed {}
-
-    #[maybe_async_cfg::maybe(sync(not(feature = "async")), async(feature = "async"))]
-    impl<SPI> Sealed for interface::SpiInterface<SPI> {}
-    #[maybe_async_cfg::maybe(sync(not(feature = "async")), async(feature = "async"))]
-    impl<I2C> Sealed for interface::I2cInterface<I2C> {}
+/// Blocking driver, interfaces and traits
+///
+/// Available with the `sync` feature, which is enabled by default.
+#[cfg(feature = "sync")]
+pub mod blocking {
+    pub use crate::interface::{
+        I2cInterfaceSync as I2cInterface, ReadDataSync as ReadData,
+        SpiInterfaceSync as SpiInterface, WriteDataSync as WriteData,
+    };
+    #[cfg(feature = "storage")]
+    pub use crate::SramStorageSync as SramStorage;
+    pub use crate::{Ds323xSync as Ds323x, SramRecordSync as SramRecord, SramSync as Sram};
+    pub use rtcc::{DateTimeAccess, Rtcc};
+}
 
-    impl Sealed for ic::DS3231 {}
-    impl Sealed for ic::DS3232 {}
-    impl Sealed for ic::DS3234 {}
+/// Asynchronous driver, interfaces and traits
+///
+/// Available with the `async` feature. It can be enabled together with the
+/// `sync` feature to use both drivers in the same binary. The async driver is
+/// only available from this module: the crate root re-exports the blocking
+/// driver when the `sync` feature is enabled.
+#[cfg(feature = "async")]
+pub mod asynch {
+    pub use crate::ds323x::{AsyncDateTimeAccess, AsyncRtcc};
+    pub use crate::interface::{
+        I2cInterfaceAsync as I2cInterface, ReadDataAsync as ReadData,
+        SpiInterfaceAsync as SpiInterface, WriteDataAsync as WriteData,
+    };
+    #[cfg(feature = "storage")]
+    pub use crate::SramStorageAsync as SramStorage;
+    pub use crate::{Ds323xAsync as Ds323x, SramAsync as Sram, SramRecordAsync as SramRecord};
 }
+
+// The blocking driver is available at the crate root for compatibility
+#[cfg(all(feature = "sync", feature = "storage"))]
+pub use crate::blocking::SramStorage;
+#[cfg(feature = "sync")]
+pub use crate::blocking::{Ds323x, Sram, SramRecord};
@@ -0,0 +1,185 @@
+//! CRC-protected record storage in the battery-backed SRAM
+maybe_async_cfg::content! {
+#![maybe_async_cfg::default(
+    idents(Sram, SramRecord),
+)]
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+use crate::Sram;
+use crate::Error;
+
+const RECORD_MAGIC: [u8; 2] = [0xD3, 0x23];
+/// Size of the record header (magic, version, length and CRC) in bytes
+pub const RECORD_HEADER_SIZE: usize = 6;
+
+/// Versioned, CRC-checked record stored in the battery-backed SRAM
+///
+/// The record is stored at a fixed offset of the SRAM as a header followed
+/// by the payload:
+/// - Magic (2 bytes)
+/// - Version (1 byte)
+/// - Payload length (1 byte)
+/// - CRC-16/CCITT-FALSE of the version, length and payload (2 bytes, big-endian)
+/// - Payload
+///
+/// When loading, a missing magic is reported as `Error::RecordNotFound`
+/// (e.g. the SRAM lost its contents), a CRC mismatch as
+/// `Error::RecordCorrupted` and a valid record of a different version as
+/// `Error::RecordVersionMismatch`.
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+#[derive(Debug)]
+pub struct SramRecord<S> {
+    sram: S,
+    offset: u8,
+    version: u8,
+}
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+impl<S> SramRecord<S> {
+    /// Create a new record at `offset` of the SRAM with the given payload version.
+    pub fn new(sram: S, offset: u8, version: u8) -> Self {
+        SramRecord {
+            sram,
+            offset,
+            version,
+        }
+    }
+
+    /// Destroy the record instance, return the driver instance.
+    pub fn destroy(self) -> S {
+        self.sram
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+impl<S, E> SramRecord<S>
+where
+    S: Sram<Error = Error<E>>,
+{
+    /// Maximum payload length that fits in the SRAM after the header.
+    pub fn max_payload_len(&self) -> usize {
+        let available = self.sram.capacity().saturating_sub(usize::from(self.offset));
+        available
+            .saturating_sub(RECORD_HEADER_SIZE)
+            .min(usize::from(u8::MAX))
+    }
+
+    fn payload_offset(&self) -> u8 {
+        self.offset.wrapping_add(RECORD_HEADER_SIZE as u8)
+    }
+
+    /// Store the payload.
+    ///
+    /// Will return an `Error::InvalidInputData` if the payload is longer than
+    /// [`max_payload_len()`](#method.max_payload_len).
+    pub async fn store(&mut self, payload: &[u8]) -> Result<(), Error<E>> {
+        let header_fits =
+            usize::from(self.offset) + RECORD_HEADER_SIZE <= self.sram.capacity();
+        if !header_fits || payload.len() > self.max_payload_len() {
+            return Err(Error::InvalidInputData);
+        }
+        let length = payload.len() as u8;
+        let crc = crc16_update(crc16_update(CRC16_INIT, &[self.version, length]), payload);
+        let crc = crc.to_be_bytes();
+        let header = [
+            RECORD_MAGIC[0],
+            RECORD_MAGIC[1],
+            self.version,
+            length,
+            crc[0],
+            crc[1],
+        ];
+        // write the payload first so that an interrupted store never leaves
+        // a valid header in front of stale data
+        self.sram.write(self.payload_offset(), payload).await?;
+        self.sram.write(self.offset, &header).await
+    }
+
+    /// Load the payload into `payload` and return its length.
+    ///
+    /// Will return an `Error::InvalidInputData` if the stored payload does
+    /// not fit in `payload`.
+    pub async fn load(&mut self, payload: &mut [u8]) -> Result<usize, Error<E>> {
+        let mut header = [0; RECORD_HEADER_SIZE];
+        self.sram.read(self.offset, &mut header).await?;
+        if header[0..2] != RECORD_MAGIC {
+            return Err(Error::RecordNotFound);
+        }
+        let version = header[2];
+        let length = usize::from(header[3]);
+        if length > self.max_payload_len() {
+            return Err(Error::RecordCorrupted);
+        }
+        if length > payload.len() {
+            return Err(Error::InvalidInputData);
+        }
+        let payload = &mut payload[..length];
+        self.sram.read(self.payload_offset(), payload).await?;
+        let crc = crc16_update(crc16_update(CRC16_INIT, &header[2..4]), payload);
+        if crc.to_be_bytes() != header[4..6] {
+            return Err(Error::RecordCorrupted);
+        }
+        if version != self.version {
+            return Err(Error::RecordVersionMismatch);
+        }
+        Ok(length)
+    }
+
+    /// Invalidate the stored record by clearing its header.
+    ///
+    /// A subsequent [`load()`](#method.load) will return `Error::RecordNotFound`.
+    pub async fn invalidate(&mut self) -> Result<(), Error<E>> {
+        self.sram.fill(self.offset, RECORD_HEADER_SIZE, 0).await
+    }
+}
+}
+
+const CRC16_INIT: u16 = 0xFFFF;
+
+// CRC-16/CCITT-FALSE (polynomial 0x1021)
+fn crc16_update(mut crc: u16, data: &[u8]) -> u16 {
+    for byte in data {
+        crc ^= u16::from(*byte) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc16_check_value() {
+        assert_eq!(0x29B1, crc16_update(CRC16_INIT, b"123456789"));
+    }
+
+    #[test]
+    fn crc16_can_be_computed_in_parts() {
+        let crc = crc16_update(crc16_update(CRC16_INIT, b"1234"), b"56789");
+        assert_eq!(0x29B1, crc);
+    }
+
+    #[test]
+    fn crc16_of_nothing_is_init() {
+        assert_eq!(CRC16_INIT, crc16_update(CRC16_INIT, &[]));
+    }
+}
@@ -0,0 +1,246 @@
+//! Virtual alarms multiplexed on Alarm1
+use crate::{Alarm1Matching, BitFlags, Datelike, DayAlarm1, Error, NaiveDateTime, Timelike};
+
+/// Set of virtual alarms that fired, as returned by `service_alarms()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FiredAlarms<const N: usize> {
+    fired: [bool; N],
+}
+
+impl<const N: usize> FiredAlarms<N> {
+    fn new() -> Self {
+        FiredAlarms { fired: [false; N] }
+    }
+
+    /// Whether the virtual alarm with the given id fired.
+    pub fn contains(&self, id: usize) -> bool {
+        self.fired.get(id).copied().unwrap_or(false)
+    }
+
+    /// Whether no virtual alarm fired.
+    pub fn is_empty(&self) -> bool {
+        !self.fired.contains(&true)
+    }
+
+    /// Iterate over the ids of the virtual alarms that fired.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.fired
+            .iter()
+            .enumerate()
+            .filter_map(|(id, fired)| if *fired { Some(id) } else { None })
+    }
+}
+
+/// Scheduler of up to `N` one-shot virtual alarms on top of Alarm1
+///
+/// The virtual alarms are kept in a fixed-capacity table and the earliest one
+/// is always programmed into Alarm1 with `set_alarm1_day()`. Virtual alarms
+/// are added and removed with the driver methods `schedule_alarm()` and
+/// `cancel_alarm()`. Once Alarm1 has matched, `service_alarms()` returns the
+/// virtual alarms that fired and programs the next one.
+///
+/// The scheduler only holds the table, so the same type is used with the
+/// blocking and the async drivers. It must not be used to set Alarm1
+/// elsewhere while the scheduler is in use. Alarm1 interrupts are enabled
+/// while a virtual alarm is scheduled and disabled once none is left, so that
+/// Alarm1 does not match again on the next month.
+///
+/// The Alarm1 matched flag is cleared when Alarm1 is programmed for a
+/// different virtual alarm that has not been reached yet. A pending match of a
+/// virtual alarm that is due is thus kept until `service_alarms()` is called.
+///
+/// Virtual alarms more than a month away can make Alarm1 match earlier on the
+/// same day of the month and time. In this case `service_alarms()` does not
+/// report any fired virtual alarm and programs Alarm1 again.
+#[derive(Debug, Clone)]
+pub struct AlarmScheduler<const N: usize> {
+    alarms: [Option<NaiveDateTime>; N],
+    armed: Option<NaiveDateTime>,
+}
+
+impl<const N: usize> Default for AlarmScheduler<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> AlarmScheduler<N> {
+    /// Create a new scheduler without any virtual alarm.
+    pub fn new() -> Self {
+        AlarmScheduler {
+            alarms: [None; N],
+            armed: None,
+        }
+    }
+
+    /// Date and time of the virtual alarm with the given id, if it is scheduled.
+    pub fn get(&self, id: usize) -> Option<NaiveDateTime> {
+        self.alarms.get(id).copied().flatten()
+    }
+
+    /// Id and date and time of the earliest scheduled virtual alarm, if any.
+    pub fn next(&self) -> Option<(usize, NaiveDateTime)> {
+        self.alarms
+            .iter()
+            .enumerate()
+            .filter_map(|(id, alarm)| alarm.map(|when| (id, when)))
+            .min_by_key(|(_, when)| *when)
+    }
+}
+
+maybe_async_cfg::content! {
+#![maybe_async_cfg::default(
+    idents(
+        ReadData, WriteData, Ds323x,
+        DateTimeAccess(sync, async = "AsyncDateTimeAccess"),
+    ),
+)]
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+use crate::{
+    interface::{ReadData, WriteData},
+    DateTimeAccess, Ds323x,
+};
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+impl<DI, IC, E> Ds323x<DI, IC>
+where
+    DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+{
+    /// Schedule a virtual alarm in an [`AlarmScheduler`] and return its id.
+    ///
+    /// Any fraction of a second is ignored.
+    ///
+    /// Will return an `Error::InvalidInputData` if `when` is not in the future
+    /// and an `Error::SchedulerFull` if `N` virtual alarms are already scheduled.
+    ///
+    /// Alarm1 is then programmed for the earliest virtual alarm. Will return an
+    /// `Error::AlarmMissed` if `when` was reached in the meantime. In this
+    /// case, the virtual alarm is not scheduled.
+    pub async fn schedule_alarm<const N: usize>(
+        &mut self,
+        scheduler: &mut AlarmScheduler<N>,
+        when: NaiveDateTime,
+    ) -> Result<usize, Error<E>> {
+        let id = scheduler
+            .alarms
+            .iter()
+            .position(Option::is_none)
+            .ok_or(Error::SchedulerFull)?;
+        let when = when.with_nanosecond(0).ok_or(Error::InvalidInputData)?;
+        let now = self.datetime().await?;
+        if when <= now {
+            return Err(Error::InvalidInputData);
+        }
+        scheduler.alarms[id] = Some(when);
+        match self.arm_scheduler(scheduler, now).await {
+            Err(Error::AlarmMissed) if scheduler.next().map(|(next, _)| next) == Some(id) => {
+                scheduler.alarms[id] = None;
+                // Program the next virtual alarm instead, which is reported by
+                // service_alarms() if it was reached as well.
+                let now = self.datetime().await?;
+                match self.arm_scheduler(scheduler, now).await {
+                    Ok(()) | Err(Error::AlarmMissed) => Err(Error::AlarmMissed),
+                    Err(e) => Err(e),
+                }
+            }
+            // An earlier virtual alarm is already due and will be reported by service_alarms()
+            Err(Error::AlarmMissed) => Ok(id),
+            result => result.map(|_| id),
+        }
+    }
+
+    /// Cancel the virtual alarm of an [`AlarmScheduler`] with the given id.
+    ///
+    /// Nothing is done if the virtual alarm is not scheduled. Otherwise,
+    /// Alarm1 is programmed for the earliest remaining virtual alarm. Will
+    /// return an `Error::AlarmMissed` if it was reached in the meantime. It is
+    /// then reported by the next call to
+    /// [`service_alarms()`](#method.service_alarms).
+    pub async fn cancel_alarm<const N: usize>(
+        &mut self,
+        scheduler: &mut AlarmScheduler<N>,
+        id: usize,
+    ) -> Result<(), Error<E>> {
+        match scheduler.alarms.get_mut(id) {
+            Some(alarm @ Some(_)) => {
+                *alarm = None;
+                let now = self.datetime().await?;
+                self.arm_scheduler(scheduler, now).await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Return the virtual alarms of an [`AlarmScheduler`] that fired and
+    /// program the next one.
+    ///
+    /// This should be called once Alarm1 has matched (see
+    /// [`has_alarm1_matched()`](#method.has_alarm1_matched)) but can be called
+    /// at any time. The Alarm1 matched flag is cleared and all virtual alarms
+    /// that are due are returned and removed from the table. Virtual alarms
+    /// reached while Alarm1 is being programmed are returned as well.
+    pub async fn service_alarms<const N: usize>(
+        &mut self,
+        scheduler: &mut AlarmScheduler<N>,
+    ) -> Result<FiredAlarms<N>, Error<E>> {
+        self.take_matched_flags(BitFlags::ALARM1F).await?;
+        let mut fired = FiredAlarms::new();
+        // Each iteration removes at least one virtual alarm when it is missed.
+        for _ in 0..=N {
+            let now = self.datetime().await?;
+            for (id, alarm) in scheduler.alarms.iter_mut().enumerate() {
+                if alarm.is_some_and(|when| when <= now) {
+                    *alarm = None;
+                    fired.fired[id] = true;
+                }
+            }
+            match self.arm_scheduler(scheduler, now).await {
+                Err(Error::AlarmMissed) => continue,
+                result => return result.map(|_| fired),
+            }
+        }
+        Ok(fired)
+    }
+
+    async fn arm_scheduler<const N: usize>(
+        &mut self,
+        scheduler: &mut AlarmScheduler<N>,
+        now: NaiveDateTime,
+    ) -> Result<(), Error<E>> {
+        let enabled = (self.control & BitFlags::ALARM1_INT_EN) != 0;
+        let when = match scheduler.next() {
+            Some((_, when)) => when,
+            None => {
+                scheduler.armed = None;
+                if enabled {
+                    return self.disable_alarm1_interrupts().await;
+                }
+                return Ok(());
+            }
+        };
+        // A flag left over from another alarm would be reported as a match but
+        // a flag set by a virtual alarm that is due must be kept.
+        if scheduler.armed != Some(when) && when > now {
+            self.clear_alarm1_matched_flag().await?;
+        }
+        let alarm = DayAlarm1::at(when.day() as u8, when.time());
+        self.set_alarm1_day(alarm, Alarm1Matching::AllMatch).await?;
+        scheduler.armed = Some(when);
+        if !enabled {
+            self.enable_alarm1_interrupts().await?;
+        }
+        if self.datetime().await? < when {
+            Ok(())
+        } else {
+            Err(Error::AlarmMissed)
+        }
+    }
+}
+}
@@ -0,0 +1,382 @@
+//! Register-level simulator of the DS3231, DS3232 and DS3234 devices
+//!
+//! Available with the `sim` feature.
+//!
+//! The [`Simulator`] models the register file of the selected device:
+//! - BCD timekeeping in 12-hour and 24-hour format, driven by a virtual clock
+//!   which is only advanced with [`advance()`](Simulator::advance).
+//! - Alarm matching, setting the alarm matched flags.
+//! - The oscillator stop flag, which is set at power-on and when the oscillator
+//!   is stopped on battery power.
+//! - Forced and automatic temperature conversions of the temperature set
+//!   with [`set_temperature()`](Simulator::set_temperature).
+//! - The battery-backed SRAM of the DS3232 and DS3234.
+//! - The INT/SQW output, which goes low on an enabled alarm match in interrupt
+//!   mode and outputs the configured square wave otherwise, and the 32kHz
+//!   output.
+//!
+//! The device is accessed through [`SimI2c`] (DS3231 and DS3232) or
+//! [`SimSpi`] (DS3234), which implement the `embedded-hal` I2C and SPI device
+//! traits and the `embedded-hal-async` ones with the `async` feature.
+//! The outputs are read through [`SimPin`], which implements the
+//! `embedded-hal` `InputPin` trait and the `embedded-hal-async` `Wait` trait.
+//! The simulator can be inspected and changed while the driver owns the bus.
+//!
+//! ```
+//! use core::time::Duration;
+//! use ds323x::{sim::Simulator, DateTimeAccess, Ds323x, NaiveDate};
+//!
+//! let sim = Simulator::new_ds3231();
+//! let mut rtc = Ds323x::new_ds3231(sim.i2c());
+//! let datetime = NaiveDate::from_ymd_opt(2024, 2, 28)
+//!     .unwrap()
+//!     .and_hms_opt(23, 59, 59)
+//!     .unwrap();
+//! rtc.set_datetime(&datetime).unwrap();
+//!
+//! sim.advance(Duration::from_secs(1));
+//! let expected = NaiveDate::from_ymd_opt(2024, 2, 29)
+//!     .unwrap()
+//!     .and_hms_opt(0, 0, 0)
+//!     .unwrap();
+//! assert_eq!(expected, rtc.datetime().unwrap());
+//! ```
+mod pin;
+mod state;
+
+pub use self::pin::SimPin;
+use self::{pin::Pin, state::State};
+use crate::{Chip, DEVICE_ADDRESS};
+use core::{cell::RefCell, time::Duration};
+use embedded_hal::{i2c, spi};
+
+/// Simulated DS3231, DS3232 or DS3234 device
+pub struct Simulator {
+    state: RefCell<State>,
+    // Tasks waiting on the INT/SQW and 32kHz outputs
+    #[cfg(feature = "async")]
+    wakers: RefCell<[Option<core::task::Waker>; 2]>,
+}
+
+impl Simulator {
+    /// Create a simulated device in its power-on state.
+    ///
+    /// The date and time is 2000-01-01 00:00:00 and the temperature is 25°C.
+    pub fn new(chip: Chip) -> Self {
+        Simulator {
+            state: RefCell::new(State::new(chip)),
+            #[cfg(feature = "async")]
+            wakers: RefCell::new([None, None]),
+        }
+    }
+
+    /// Create a simulated DS3231 device.
+    pub fn new_ds3231() -> Self {
+        Self::new(Chip::DS3231)
+    }
+
+    /// Create a simulated DS3232 device.
+    pub fn new_ds3232() -> Self {
+        Self::new(Chip::DS3232)
+    }
+
+    /// Create a simulated DS3234 device.
+    pub fn new_ds3234() -> Self {
+        Self::new(Chip::DS3234)
+    }
+
+    /// Simulated chip.
+    pub fn chip(&self) -> Chip {
+        self.state.borrow().chip
+    }
+
+    /// I2C bus with the simulated device.
+    ///
+    /// A DS3234 does not acknowledge its address on this bus.
+    pub fn i2c(&self) -> SimI2c<'_> {
+        SimI2c { sim: self }
+    }
+
+    /// SPI device of the simulated device.
+    ///
+    /// A DS3231 or DS3232 does not respond on this bus: writes are ignored and
+    /// reads return `0xFF`.
+    pub fn spi(&self) -> SimSpi<'_> {
+        SimSpi {
+            sim: self,
+            address: None,
+        }
+    }
+
+    /// INT/SQW output of the simulated device.
+    ///
+    /// With the interrupt control bit set, the output is low while the alarm
+    /// matched flag of an alarm with its interrupt enabled is set. Otherwise it
+    /// outputs the square wave at the configured frequency. On battery power the
+    /// square wave is only output if it is enabled on battery power.
+    pub fn int_sqw(&self) -> SimPin<'_> {
+        SimPin {
+            sim: self,
+            pin: Pin::IntSqw,
+        }
+    }
+
+    /// 32kHz output of the simulated device.
+    ///
+    /// The output is a 32.768kHz square wave while it is enabled. On battery
+    /// power, this is only the case for the DS3231 or if it is enabled on
+    /// battery power.
+    pub fn out_32khz(&self) -> SimPin<'_> {
+        SimPin {
+            sim: self,
+            pin: Pin::Out32kHz,
+        }
+    }
+
+    /// Advance the virtual clock.
+    ///
+    /// The time registers are updated and the alarms are checked once per
+    /// elapsed second, unless the oscillator is stopped. The square wave
+    /// outputs follow the virtual clock.
+    pub fn advance(&self, duration: Duration) {
+        self.state.borrow_mut().advance(duration);
+        self.wake();
+    }
+
+    /// Time elapsed since the last increment of the seconds register.
+    pub fn subsecond(&self) -> Duration {
+        self.state.borrow().subsecond()
+    }
+
+    /// Set the temperature measured by the device in °C.
+    ///
+    /// It is rounded to 0.25°C and stored in the temperature registers by
+    /// the next conversion.
+    pub fn set_temperature(&self, celsius: f32) {
+        let quarters = celsius * 4.0;
+        let quarters = if quarters < 0.0 {
+            quarters - 0.5
+        } else {
+            quarters + 0.5
+        };
+        // 10-bit two's complement
+        self.state.borrow_mut().temperature = (quarters as i16).clamp(-512, 511);
+    }
+
+    /// Switch the device between main and battery power.
+    ///
+    /// The bus interface is inactive on battery power. The oscillator stops
+    /// and the oscillator stop flag is set if the oscillator is disabled on
+    /// battery power.
+    pub fn set_battery_powered(&self, on_battery: bool) {
+        self.state.borrow_mut().set_on_battery(on_battery);
+        self.wake();
+    }
+
+    /// Value of a register without side effects.
+    ///
+    /// For the DS3232, the SRAM is available at the addresses 0x14-0xFF.
+    pub fn register(&self, address: u8) -> u8 {
+        self.state.borrow().registers[usize::from(address)]
+    }
+
+    /// Set the value of a register without side effects.
+    pub fn set_register(&self, address: u8, value: u8) {
+        let mut state = self.state.borrow_mut();
+        state.registers[usize::from(address)] = value;
+        state.update_pins();
+        drop(state);
+        self.wake();
+    }
+
+    /// Value of an SRAM byte of the DS3234.
+    pub fn sram(&self, offset: u8) -> u8 {
+        self.state.borrow().sram[usize::from(offset)]
+    }
+
+    // Wakes the tasks waiting on the outputs after the state has changed
+    fn wake(&self) {
+        #[cfg(feature = "async")]
+        {
+            let wakers = core::mem::take(&mut *self.wakers.borrow_mut());
+            for waker in IntoIterator::into_iter(wakers).flatten() {
+                waker.wake();
+            }
+        }
+    }
+
+    fn i2c_transaction(
+        &self,
+        address: u8,
+        operations: &mut [i2c::Operation<'_>],
+    ) -> Result<(), i2c::ErrorKind> {
+        let result = self.i2c_operations(address, operations);
+        self.wake();
+        result
+    }
+
+    fn i2c_operations(
+        &self,
+        address: u8,
+        operations: &mut [i2c::Operation<'_>],
+    ) -> Result<(), i2c::ErrorKind> {
+        let mut state = self.state.borrow_mut();
+        if address != DEVICE_ADDRESS || state.chip == Chip::DS3234 || state.on_battery {
+            return Err(i2c::ErrorKind::NoAcknowledge(
+                i2c::NoAcknowledgeSource::Address,
+            ));
+        }
+        let mut previous_was_write = false;
+        for operation in operations {
+            match operation {
+                i2c::Operation::Write(data) => {
+                    let mut data = data.iter();
+                    if !previous_was_write {
+                        if let Some(pointer) = data.next() {
+                            state.pointer = *pointer;
+                        }
+                    }
+                    for value in data {
+                        let pointer = state.pointer;
+                        state.write(pointer, *value);
+                        state.pointer = state.next_address(pointer);
+                    }
+                    previous_was_write = true;
+                }
+                i2c::Operation::Read(buffer) => {
+                    for value in buffer.iter_mut() {
+                        let pointer = state.pointer;
+                        *value = state.read(pointer);
+                        state.pointer = state.next_address(pointer);
+                    }
+                    previous_was_write = false;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn spi_transfer(&self, address: &mut Option<u8>, value: u8) -> u8 {
+        let mut state = self.state.borrow_mut();
+        if state.chip != Chip::DS3234 || state.on_battery {
+            return 0xFF;
+        }
+        match *address {
+            None => {
+                *address = Some(value);
+                0
+            }
+            Some(current) => {
+                let register = current & 0x7F;
+                let next = (current & 0x80) | state.next_address(register);
+                *address = Some(next);
+                if (current & 0x80) != 0 {
+                    state.write(register, value);
+                    0
+                } else {
+                    state.read(register)
+                }
+            }
+        }
+    }
+}
+
+/// I2C bus of a [`Simulator`]
+pub struct SimI2c<'a> {
+    sim: &'a Simulator,
+}
+
+impl i2c::ErrorType for SimI2c<'_> {
+    type Error = i2c::ErrorKind;
+}
+
+impl i2c::I2c for SimI2c<'_> {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.sim.i2c_transaction(address, operations)
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::i2c::I2c for SimI2c<'_> {
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.sim.i2c_transaction(address, operations)
+    }
+}
+
+/// SPI device of a [`Simulator`]
+///
+/// The chip select is asserted for the duration of each transaction.
+pub struct SimSpi<'a> {
+    sim: &'a Simulator,
+    // Address byte received in the current transaction
+    address: Option<u8>,
+}
+
+impl SimSpi<'_> {
+    fn transaction(&mut self, operations: &mut [spi::Operation<'_, u8>]) {
+        self.address = None;
+        for operation in operations {
+            match operation {
+                spi::Operation::Read(buffer) => {
+                    for value in buffer.iter_mut() {
+                        *value = self.sim.spi_transfer(&mut self.address, 0);
+                    }
+                }
+                spi::Operation::Write(data) => {
+                    for value in data.iter() {
+                        self.sim.spi_transfer(&mut self.address, *value);
+                    }
+                }
+                spi::Operation::Transfer(read, write) => {
+                    for i in 0..read.len().max(write.len()) {
+                        let out = write.get(i).copied().unwrap_or(0);
+                        let value = self.sim.spi_transfer(&mut self.address, out);
+                        if let Some(read) = read.get_mut(i) {
+                            *read = value;
+                        }
+                    }
+                }
+                spi::Operation::TransferInPlace(buffer) => {
+                    for value in buffer.iter_mut() {
+                        *value = self.sim.spi_transfer(&mut self.address, *value);
+                    }
+                }
+                spi::Operation::DelayNs(_) => (),
+            }
+        }
+        self.sim.wake();
+    }
+}
+
+impl spi::ErrorType for SimSpi<'_> {
+    type Error = spi::ErrorKind;
+}
+
+impl spi::SpiDevice<u8> for SimSpi<'_> {
+    fn transaction(
+        &mut self,
+        operations: &mut [spi::Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        SimSpi::transaction(self, operations);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::spi::SpiDevice<u8> for SimSpi<'_> {
+    async fn transaction(
+        &mut self,
+        operations: &mut [spi::Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        SimSpi::transaction(self, operations);
+        Ok(())
+    }
+}
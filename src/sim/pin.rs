@@ -0,0 +1,88 @@
+//! Output pins of the simulated device
+use super::{state::PinState, Simulator};
+use core::convert::Infallible;
+use embedded_hal::digital;
+
+#[derive(Clone, Copy)]
+pub(crate) enum Pin {
+    IntSqw = 0,
+    Out32kHz = 1,
+}
+
+/// Output pin of a [`Simulator`]
+///
+/// Both outputs are open drain. They read high while they are not driven low,
+/// as with an external pull-up resistor.
+pub struct SimPin<'a> {
+    pub(crate) sim: &'a Simulator,
+    pub(crate) pin: Pin,
+}
+
+impl SimPin<'_> {
+    fn state(&self) -> PinState {
+        let state = self.sim.state.borrow();
+        match self.pin {
+            Pin::IntSqw => state.int_sqw,
+            Pin::Out32kHz => state.out_32khz,
+        }
+    }
+
+    #[cfg(feature = "async")]
+    async fn wait(&self, condition: impl Fn(&PinState) -> bool) -> Result<(), Infallible> {
+        core::future::poll_fn(|cx| {
+            if condition(&self.state()) {
+                core::task::Poll::Ready(Ok(()))
+            } else {
+                self.sim.wakers.borrow_mut()[self.pin as usize] = Some(cx.waker().clone());
+                core::task::Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+impl digital::ErrorType for SimPin<'_> {
+    type Error = Infallible;
+}
+
+impl digital::InputPin for SimPin<'_> {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.state().high)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.state().high)
+    }
+}
+
+/// The futures complete once the simulator has been changed such that the
+/// condition is met, for example with [`advance()`](Simulator::advance).
+/// Only the last task waiting on each pin is woken.
+#[cfg(feature = "async")]
+impl embedded_hal_async::digital::Wait for SimPin<'_> {
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        self.wait(|pin| pin.high).await
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        self.wait(|pin| !pin.high).await
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        let start = self.state().rising_edges;
+        self.wait(|pin| pin.rising_edges != start).await
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        let start = self.state().falling_edges;
+        self.wait(|pin| pin.falling_edges != start).await
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        let start = self.state();
+        self.wait(|pin| {
+            pin.rising_edges != start.rising_edges || pin.falling_edges != start.falling_edges
+        })
+        .await
+    }
+}
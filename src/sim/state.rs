@@ -0,0 +1,479 @@
+//! Register file and timekeeping model of the simulated device
+use crate::ds323x::{decimal_to_packed_bcd, packed_bcd_to_decimal};
+use crate::{BitFlags, Chip, Register};
+use core::time::Duration;
+
+// Time needed by a forced temperature conversion
+const CONVERSION_TIME: Duration = Duration::from_millis(200);
+// Last timekeeping, control and temperature register of the DS3231
+const DS3231_LAST_REGISTER: u8 = Register::TEMP_LSB;
+// Status bits that can be written on the DS3231
+const DS3231_STATUS_MASK: u8 =
+    BitFlags::OSC_STOP | BitFlags::EN32KHZ | BitFlags::ALARM2F | BitFlags::ALARM1F;
+// Status bits that can only be cleared
+const STATUS_CLEAR_ONLY: u8 = BitFlags::OSC_STOP | BitFlags::ALARM2F | BitFlags::ALARM1F;
+const NANOS_PER_SECOND: u128 = 1_000_000_000;
+
+// Signal on an output pin
+#[derive(Clone, Copy)]
+enum Output {
+    // Static level, high if true. Also used for high impedance (pulled up).
+    Level(bool),
+    // Square wave of the given frequency in Hz
+    Wave(u32),
+}
+
+// Level and number of edges of an output pin
+#[derive(Clone, Copy, Default)]
+pub(crate) struct PinState {
+    pub(crate) high: bool,
+    pub(crate) falling_edges: u64,
+    pub(crate) rising_edges: u64,
+}
+
+impl PinState {
+    fn set(&mut self, high: bool) {
+        if high && !self.high {
+            self.rising_edges += 1;
+        } else if !high && self.high {
+            self.falling_edges += 1;
+        }
+        self.high = high;
+    }
+}
+
+pub(crate) struct State {
+    pub(crate) chip: Chip,
+    // DS3231/DS3232: registers and SRAM at their addresses.
+    // DS3234: registers 0x00-0x19.
+    pub(crate) registers: [u8; 0x100],
+    // DS3234 SRAM
+    pub(crate) sram: [u8; 0x100],
+    // Register pointer of the I2C interface
+    pub(crate) pointer: u8,
+    // Temperature in steps of 0.25°C, copied to the registers on conversion
+    pub(crate) temperature: i16,
+    pub(crate) on_battery: bool,
+    // Time the oscillator has been running, aligned to the seconds updates
+    clock: Duration,
+    pub(crate) int_sqw: PinState,
+    pub(crate) out_32khz: PinState,
+    // Remaining time of a forced temperature conversion
+    conversion: Option<Duration>,
+    // Seconds elapsed since the last automatic temperature conversion
+    since_conversion: u32,
+}
+
+impl State {
+    pub(crate) fn new(chip: Chip) -> Self {
+        let mut state = State {
+            chip,
+            registers: [0; 0x100],
+            sram: [0; 0x100],
+            pointer: 0,
+            temperature: 25 * 4,
+            on_battery: false,
+            clock: Duration::ZERO,
+            int_sqw: PinState::default(),
+            out_32khz: PinState::default(),
+            conversion: None,
+            since_conversion: 0,
+        };
+        // 01/01/00 00:00:00, Monday
+        state.registers[usize::from(Register::DOW)] = 1;
+        state.registers[usize::from(Register::DOM)] = 1;
+        state.registers[usize::from(Register::MONTH)] = 1;
+        state.registers[usize::from(Register::CONTROL)] = crate::CONTROL_POR_VALUE;
+        state.registers[usize::from(Register::STATUS)] = match chip {
+            Chip::DS3231 => BitFlags::OSC_STOP | BitFlags::EN32KHZ,
+            _ => BitFlags::OSC_STOP | BitFlags::BB32KHZ | BitFlags::EN32KHZ,
+        };
+        state.update_temperature_registers();
+        state.int_sqw.high = state.level(state.int_sqw_output());
+        state.out_32khz.high = state.level(state.out_32khz_output());
+        state
+    }
+
+    pub(crate) fn control(&self) -> u8 {
+        self.registers[usize::from(Register::CONTROL)]
+    }
+
+    pub(crate) fn status(&self) -> u8 {
+        self.registers[usize::from(Register::STATUS)]
+    }
+
+    fn set_status_bits(&mut self, bits: u8) {
+        self.registers[usize::from(Register::STATUS)] |= bits;
+    }
+
+    pub(crate) fn oscillator_running(&self) -> bool {
+        !(self.on_battery && (self.control() & BitFlags::EOSC) != 0)
+    }
+
+    pub(crate) fn set_on_battery(&mut self, on_battery: bool) {
+        self.on_battery = on_battery;
+        if !self.oscillator_running() {
+            self.set_status_bits(BitFlags::OSC_STOP);
+        }
+        self.update_pins();
+    }
+
+    // Address of the register accessed after `address`
+    pub(crate) fn next_address(&self, address: u8) -> u8 {
+        match self.chip {
+            Chip::DS3231 if address == DS3231_LAST_REGISTER => 0,
+            // the SRAM data register is accessed repeatedly
+            Chip::DS3234 if address == Register::SRAM_DATA => address,
+            _ => address.wrapping_add(1),
+        }
+    }
+
+    pub(crate) fn read(&mut self, address: u8) -> u8 {
+        match (self.chip, address) {
+            (Chip::DS3231, a) if a > DS3231_LAST_REGISTER => 0,
+            (Chip::DS3232, Register::TEMP_CONV) => 0,
+            (Chip::DS3234, Register::SRAM_DATA) => {
+                let sram_address = &mut self.registers[usize::from(Register::SRAM_ADDRESS)];
+                let value = self.sram[usize::from(*sram_address)];
+                *sram_address = sram_address.wrapping_add(1);
+                value
+            }
+            (Chip::DS3234, a) if a > Register::SRAM_DATA || (0x14..0x18).contains(&a) => 0,
+            (_, a) => self.registers[usize::from(a)],
+        }
+    }
+
+    pub(crate) fn write(&mut self, address: u8, value: u8) {
+        let value = match (self.chip, address) {
+            (_, Register::SECONDS) => {
+                // the countdown chain is reset when the seconds are written
+                self.clock = Duration::from_secs(self.clock.as_secs());
+                value & 0x7F
+            }
+            (_, Register::MINUTES) | (_, Register::HOURS) => value & 0x7F,
+            (_, Register::DOW) => value & 0x07,
+            (_, Register::DOM) => value & 0x3F,
+            (_, Register::MONTH) => value & 0x9F,
+            (_, Register::CONTROL) => self.write_control(value),
+            (_, Register::STATUS) => self.write_status(value),
+            (_, Register::TEMP_MSB) | (_, Register::TEMP_LSB) => return,
+            (Chip::DS3231, a) if a > DS3231_LAST_REGISTER => return,
+            (Chip::DS3232, Register::TEMP_CONV) => return,
+            (Chip::DS3234, Register::TEMP_CONV) => value & BitFlags::TEMP_CONV_BAT,
+            (Chip::DS3234, Register::SRAM_DATA) => {
+                let sram_address = &mut self.registers[usize::from(Register::SRAM_ADDRESS)];
+                self.sram[usize::from(*sram_address)] = value;
+                *sram_address = sram_address.wrapping_add(1);
+                return;
+            }
+            (Chip::DS3234, a) if a > Register::SRAM_DATA || (0x14..0x18).contains(&a) => return,
+            _ => value,
+        };
+        self.registers[usize::from(address)] = value;
+        self.update_pins();
+    }
+
+    fn write_control(&mut self, value: u8) -> u8 {
+        if self.conversion.is_some() {
+            // the conversion in progress cannot be cancelled
+            value | BitFlags::TEMP_CONV
+        } else if (value & BitFlags::TEMP_CONV) != 0 {
+            self.conversion = Some(CONVERSION_TIME);
+            self.set_status_bits(BitFlags::BUSY);
+            value
+        } else {
+            value
+        }
+    }
+
+    fn write_status(&mut self, value: u8) -> u8 {
+        let current = self.status();
+        let writable = match self.chip {
+            Chip::DS3231 => DS3231_STATUS_MASK,
+            _ => !BitFlags::BUSY,
+        };
+        let value = (value & writable & !STATUS_CLEAR_ONLY)
+            | (value & current & STATUS_CLEAR_ONLY)
+            | (current & BitFlags::BUSY);
+        if !self.oscillator_running() {
+            value | BitFlags::OSC_STOP
+        } else {
+            value
+        }
+    }
+
+    pub(crate) fn advance(&mut self, duration: Duration) {
+        if let Some(remaining) = self.conversion {
+            if duration >= remaining {
+                self.finish_conversion();
+            } else {
+                self.conversion = Some(remaining - duration);
+            }
+        }
+        if !self.oscillator_running() {
+            return;
+        }
+        let mut remaining = duration;
+        while remaining > Duration::ZERO {
+            let to_next_second = Duration::from_secs(1) - self.subsecond();
+            let step = remaining.min(to_next_second);
+            self.advance_clock(step);
+            if step == to_next_second {
+                self.tick();
+                self.update_pins();
+            }
+            remaining -= step;
+        }
+    }
+
+    pub(crate) fn subsecond(&self) -> Duration {
+        Duration::from_nanos(u64::from(self.clock.subsec_nanos()))
+    }
+
+    // Advances the clock counting the edges of the square waves
+    fn advance_clock(&mut self, step: Duration) {
+        let start = self.clock;
+        self.clock += step;
+        let (int_sqw, out_32khz) = (self.int_sqw_output(), self.out_32khz_output());
+        for (pin, output) in [
+            (&mut self.int_sqw, int_sqw),
+            (&mut self.out_32khz, out_32khz),
+        ] {
+            if let Output::Wave(frequency) = output {
+                let from = half_periods(start, frequency);
+                let to = half_periods(self.clock, frequency);
+                // the output is low during the even half periods
+                let falling_edges = (to / 2 - from / 2) as u64;
+                pin.falling_edges += falling_edges;
+                pin.rising_edges += (to - from) as u64 - falling_edges;
+                pin.high = to % 2 == 1;
+            }
+        }
+    }
+
+    fn int_sqw_output(&self) -> Output {
+        let control = self.control();
+        let status = self.status();
+        if (control & BitFlags::INTCN) != 0 {
+            let alarm1 = (control & status & BitFlags::ALARM1_INT_EN) != 0;
+            let alarm2 = (control & status & BitFlags::ALARM2_INT_EN) != 0;
+            Output::Level(!(alarm1 || alarm2))
+        } else if self.on_battery && (control & BitFlags::BBSQW) == 0 {
+            Output::Level(true)
+        } else {
+            Output::Wave(match control & (BitFlags::RS2 | BitFlags::RS1) {
+                0 => 1,
+                BitFlags::RS1 => 1024,
+                BitFlags::RS2 => 4096,
+                _ => 8192,
+            })
+        }
+    }
+
+    fn out_32khz_output(&self) -> Output {
+        let status = self.status();
+        let on_battery_enabled = self.chip == Chip::DS3231 || (status & BitFlags::BB32KHZ) != 0;
+        if (status & BitFlags::EN32KHZ) != 0 && (!self.on_battery || on_battery_enabled) {
+            Output::Wave(32768)
+        } else {
+            Output::Level(true)
+        }
+    }
+
+    fn level(&self, output: Output) -> bool {
+        match output {
+            Output::Level(high) => high,
+            Output::Wave(frequency) => half_periods(self.clock, frequency) % 2 == 1,
+        }
+    }
+
+    // Updates the pin levels after a change of the configuration or flags
+    pub(crate) fn update_pins(&mut self) {
+        let int_sqw = self.level(self.int_sqw_output());
+        let out_32khz = self.level(self.out_32khz_output());
+        self.int_sqw.set(int_sqw);
+        self.out_32khz.set(out_32khz);
+    }
+
+    fn finish_conversion(&mut self) {
+        self.conversion = None;
+        self.update_temperature_registers();
+        self.registers[usize::from(Register::CONTROL)] &= !BitFlags::TEMP_CONV;
+        self.registers[usize::from(Register::STATUS)] &= !BitFlags::BUSY;
+    }
+
+    pub(crate) fn update_temperature_registers(&mut self) {
+        let temperature = self.temperature;
+        self.registers[usize::from(Register::TEMP_MSB)] = (temperature >> 2) as u8;
+        self.registers[usize::from(Register::TEMP_LSB)] = ((temperature & 0b11) << 6) as u8;
+    }
+
+    // Seconds between automatic temperature conversions
+    fn conversion_period(&self) -> Option<u32> {
+        let status = self.status();
+        match self.chip {
+            Chip::DS3234
+                if self.on_battery
+                    && (self.registers[usize::from(Register::TEMP_CONV)]
+                        & BitFlags::TEMP_CONV_BAT)
+                        != 0 =>
+            {
+                None
+            }
+            Chip::DS3231 => Some(64),
+            _ => Some(64 << ((status & (BitFlags::CRATE1 | BitFlags::CRATE0)) >> 4)),
+        }
+    }
+
+    fn tick(&mut self) {
+        self.increment_time();
+        self.since_conversion += 1;
+        if let Some(period) = self.conversion_period() {
+            if self.since_conversion >= period {
+                self.since_conversion = 0;
+                self.update_temperature_registers();
+            }
+        }
+        let mut flags = 0;
+        if self.alarm1_matches() {
+            flags |= BitFlags::ALARM1F;
+        }
+        if self.alarm2_matches() {
+            flags |= BitFlags::ALARM2F;
+        }
+        self.set_status_bits(flags);
+    }
+
+    fn register(&self, address: u8) -> u8 {
+        self.registers[usize::from(address)]
+    }
+
+    fn decimal(&self, address: u8, mask: u8) -> u8 {
+        packed_bcd_to_decimal(self.register(address) & mask)
+    }
+
+    fn increment_time(&mut self) {
+        let second = self.decimal(Register::SECONDS, 0x7F) + 1;
+        if second < 60 {
+            return self.set_decimal(Register::SECONDS, second);
+        }
+        self.set_decimal(Register::SECONDS, 0);
+        let minute = self.decimal(Register::MINUTES, 0x7F) + 1;
+        if minute < 60 {
+            return self.set_decimal(Register::MINUTES, minute);
+        }
+        self.set_decimal(Register::MINUTES, 0);
+        let hours = self.register(Register::HOURS);
+        let hour = hour_from_register(hours) + 1;
+        if hour < 24 {
+            self.registers[usize::from(Register::HOURS)] = hour_to_register(hour, hours);
+            return;
+        }
+        self.registers[usize::from(Register::HOURS)] = hour_to_register(0, hours);
+        let weekday = self.register(Register::DOW);
+        self.registers[usize::from(Register::DOW)] = if weekday >= 7 { 1 } else { weekday + 1 };
+        let month_register = self.register(Register::MONTH);
+        let month = packed_bcd_to_decimal(month_register & 0x1F);
+        let year = self.decimal(Register::YEAR, 0xFF);
+        let day = self.decimal(Register::DOM, 0x3F) + 1;
+        if day <= days_in_month(month, year) {
+            return self.set_decimal(Register::DOM, day);
+        }
+        self.set_decimal(Register::DOM, 1);
+        let century = month_register & BitFlags::CENTURY;
+        if month < 12 {
+            self.registers[usize::from(Register::MONTH)] =
+                century | decimal_to_packed_bcd(month + 1);
+            return;
+        }
+        if year < 99 {
+            self.registers[usize::from(Register::MONTH)] = century | 1;
+            return self.set_decimal(Register::YEAR, year + 1);
+        }
+        self.registers[usize::from(Register::MONTH)] = (century ^ BitFlags::CENTURY) | 1;
+        self.set_decimal(Register::YEAR, 0);
+    }
+
+    fn set_decimal(&mut self, address: u8, value: u8) {
+        self.registers[usize::from(address)] = decimal_to_packed_bcd(value);
+    }
+
+    // Checks whether an alarm field matches or is masked
+    fn field_matches(&self, alarm: u8, mask: u8, current: u8) -> bool {
+        (alarm & BitFlags::ALARM_MATCH) != 0 || packed_bcd_to_decimal(alarm & mask) == current
+    }
+
+    fn hour_matches(&self, alarm: u8) -> bool {
+        let hour = hour_from_register(self.register(Register::HOURS));
+        (alarm & BitFlags::ALARM_MATCH) != 0 || hour_from_register(alarm & 0x7F) == hour
+    }
+
+    fn day_matches(&self, alarm: u8) -> bool {
+        if (alarm & BitFlags::ALARM_MATCH) != 0 {
+            true
+        } else if (alarm & BitFlags::WEEKDAY) != 0 {
+            (alarm & 0x0F) == self.register(Register::DOW)
+        } else {
+            packed_bcd_to_decimal(alarm & 0x3F) == self.decimal(Register::DOM, 0x3F)
+        }
+    }
+
+    fn alarm1_matches(&self) -> bool {
+        let alarm = &self.registers[usize::from(Register::ALARM1_SECONDS)..];
+        self.field_matches(alarm[0], 0x7F, self.decimal(Register::SECONDS, 0x7F))
+            && self.field_matches(alarm[1], 0x7F, self.decimal(Register::MINUTES, 0x7F))
+            && self.hour_matches(alarm[2])
+            && self.day_matches(alarm[3])
+    }
+
+    fn alarm2_matches(&self) -> bool {
+        let alarm = &self.registers[usize::from(Register::ALARM2_MINUTES)..];
+        self.register(Register::SECONDS) == 0
+            && self.field_matches(alarm[0], 0x7F, self.decimal(Register::MINUTES, 0x7F))
+            && self.hour_matches(alarm[1])
+            && self.day_matches(alarm[2])
+    }
+}
+
+// Number of half periods of a square wave elapsed at `clock`
+fn half_periods(clock: Duration, frequency: u32) -> u128 {
+    clock.as_nanos() * 2 * u128::from(frequency) / NANOS_PER_SECOND
+}
+
+// Hour in 24h format from an hours register in 12h or 24h format
+fn hour_from_register(register: u8) -> u8 {
+    if (register & BitFlags::H24_H12) != 0 {
+        let hour = packed_bcd_to_decimal(register & 0x1F) % 12;
+        if (register & BitFlags::AM_PM) != 0 {
+            hour + 12
+        } else {
+            hour
+        }
+    } else {
+        packed_bcd_to_decimal(register & 0x3F)
+    }
+}
+
+// Hours register keeping the 12h/24h format of `current`
+fn hour_to_register(hour: u8, current: u8) -> u8 {
+    if (current & BitFlags::H24_H12) != 0 {
+        let am_pm = if hour >= 12 { BitFlags::AM_PM } else { 0 };
+        let hour = match hour % 12 {
+            0 => 12,
+            h => h,
+        };
+        BitFlags::H24_H12 | am_pm | decimal_to_packed_bcd(hour)
+    } else {
+        decimal_to_packed_bcd(hour)
+    }
+}
+
+fn days_in_month(month: u8, year: u8) -> u8 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        // the device treats every multiple of 4 as a leap year
+        2 if (year & 0b11) == 0 => 29,
+        2 => 28,
+        _ => 31,
+    }
+}
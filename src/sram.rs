@@ -0,0 +1,96 @@
+//! Generic battery-backed SRAM access
+maybe_async_cfg::content! {
+#![maybe_async_cfg::default(
+    idents(ReadData, WriteData, Sram, Ds323x),
+)]
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+use crate::{
+    interface::{ReadData, WriteData},
+    Ds323x,
+};
+use crate::{ic, Error};
+use crate::{ds3232::DS3232_SRAM_SIZE, ds3234::DS3234_SRAM_SIZE};
+
+/// Battery-backed SRAM access.
+///
+/// This allows writing storage code once for both the DS3232 and the DS3234.
+/// It is intentionally not implemented for the DS3231, which has no SRAM.
+///
+/// Offsets are relative to the beginning of the SRAM. Any access that does
+/// not fit in [`capacity()`](#tymethod.capacity) bytes returns an
+/// `Error::InvalidInputData` without touching the device.
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+pub trait Sram {
+    /// Error type
+    type Error;
+    /// Size of the SRAM in bytes.
+    fn capacity(&self) -> usize;
+    /// Read data starting at `offset`.
+    async fn read(&mut self, offset: u8, data: &mut [u8]) -> Result<(), Self::Error>;
+    /// Write data starting at `offset`.
+    async fn write(&mut self, offset: u8, data: &[u8]) -> Result<(), Self::Error>;
+    /// Set `len` bytes starting at `offset` to `value`.
+    async fn fill(&mut self, offset: u8, len: usize, value: u8) -> Result<(), Self::Error>;
+}
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+impl<DI, E> Sram for Ds323x<DI, ic::DS3232>
+where
+    DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+{
+    type Error = Error<E>;
+
+    fn capacity(&self) -> usize {
+        DS3232_SRAM_SIZE
+    }
+
+    async fn read(&mut self, offset: u8, data: &mut [u8]) -> Result<(), Self::Error> {
+        self.read_sram(offset, data).await
+    }
+
+    async fn write(&mut self, offset: u8, data: &[u8]) -> Result<(), Self::Error> {
+        self.write_sram(offset, data).await
+    }
+
+    async fn fill(&mut self, offset: u8, len: usize, value: u8) -> Result<(), Self::Error> {
+        self.fill_sram(offset, len, value).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+impl<DI, E> Sram for Ds323x<DI, ic::DS3234>
+where
+    DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+{
+    type Error = Error<E>;
+
+    fn capacity(&self) -> usize {
+        DS3234_SRAM_SIZE
+    }
+
+    async fn read(&mut self, offset: u8, data: &mut [u8]) -> Result<(), Self::Error> {
+        self.read_sram(offset, data).await
+    }
+
+    async fn write(&mut self, offset: u8, data: &[u8]) -> Result<(), Self::Error> {
+        self.write_sram(offset, data).await
+    }
+
+    async fn fill(&mut self, offset: u8, len: usize, value: u8) -> Result<(), Self::Error> {
+        self.fill_sram(offset, len, value).await
+    }
+}
+}
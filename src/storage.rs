@@ -0,0 +1,192 @@
+//! `embedded-storage` adapter over the battery-backed SRAM
+maybe_async_cfg::content! {
+#![maybe_async_cfg::default(
+    idents(
+        Sram, SramStorage,
+        embedded_storage(sync, async = "embedded_storage_async"),
+    ),
+)]
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+use crate::Sram;
+use crate::Error;
+use core::{convert::TryFrom, fmt::Debug};
+use embedded_storage::nor_flash::{ErrorType, NorFlashError, NorFlashErrorKind};
+#[cfg(feature = "sync")]
+use embedded_storage::{ReadStorage, Storage};
+
+/// Adapter exposing the battery-backed SRAM of a DS3232 or DS3234 as
+/// `embedded-storage` storage.
+///
+/// In sync builds this implements `ReadStorage`, `Storage` and the NOR flash
+/// traits from `embedded-storage`. In async builds it implements the NOR flash
+/// traits from `embedded-storage-async`.
+///
+/// The SRAM can be read and written byte by byte, so all read, write and
+/// erase sizes are 1. Erasing sets the bytes to `0xFF`.
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+#[derive(Debug)]
+pub struct SramStorage<S> {
+    sram: S,
+}
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+impl<S> SramStorage<S> {
+    /// Create a new adapter from an SRAM-capable driver.
+    pub fn new(sram: S) -> Self {
+        SramStorage { sram }
+    }
+
+    /// Destroy the adapter, return the driver instance.
+    pub fn destroy(self) -> S {
+        self.sram
+    }
+}
+
+impl<E: Debug> NorFlashError for Error<E> {
+    fn kind(&self) -> NorFlashErrorKind {
+        match self {
+            Error::InvalidInputData => NorFlashErrorKind::OutOfBounds,
+            _ => NorFlashErrorKind::Other,
+        }
+    }
+}
+
+fn sram_offset<E>(offset: u32) -> Result<u8, Error<E>> {
+    u8::try_from(offset).map_err(|_| Error::InvalidInputData)
+}
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+impl<S, E> SramStorage<S>
+where
+    S: Sram<Error = Error<E>>,
+{
+    async fn read_bytes(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Error<E>> {
+        if bytes.is_empty() && offset as usize <= self.sram.capacity() {
+            return Ok(());
+        }
+        self.sram.read(sram_offset(offset)?, bytes).await
+    }
+
+    async fn write_bytes(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Error<E>> {
+        if bytes.is_empty() && offset as usize <= self.sram.capacity() {
+            return Ok(());
+        }
+        self.sram.write(sram_offset(offset)?, bytes).await
+    }
+
+    async fn erase_bytes(&mut self, from: u32, to: u32) -> Result<(), Error<E>> {
+        if from > to || to as usize > self.sram.capacity() {
+            return Err(Error::InvalidInputData);
+        }
+        if from == to {
+            return Ok(());
+        }
+        self.sram
+            .fill(sram_offset(from)?, (to - from) as usize, 0xFF)
+            .await
+    }
+}
+
+#[maybe_async_cfg::maybe(sync(feature = "sync"))]
+impl<S, E> ReadStorage for SramStorage<S>
+where
+    S: Sram<Error = Error<E>>,
+{
+    type Error = Error<E>;
+
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        self.read_bytes(offset, bytes)
+    }
+
+    fn capacity(&self) -> usize {
+        self.sram.capacity()
+    }
+}
+
+#[maybe_async_cfg::maybe(sync(feature = "sync"))]
+impl<S, E> Storage for SramStorage<S>
+where
+    S: Sram<Error = Error<E>>,
+{
+    fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.write_bytes(offset, bytes)
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+impl<S, E> ErrorType for SramStorage<S>
+where
+    S: Sram<Error = Error<E>>,
+    E: Debug,
+{
+    type Error = Error<E>;
+}
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+impl<S, E> embedded_storage::nor_flash::ReadNorFlash for SramStorage<S>
+where
+    S: Sram<Error = Error<E>>,
+    E: Debug,
+{
+    const READ_SIZE: usize = 1;
+
+    async fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        self.read_bytes(offset, bytes).await
+    }
+
+    fn capacity(&self) -> usize {
+        self.sram.capacity()
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+impl<S, E> embedded_storage::nor_flash::NorFlash for SramStorage<S>
+where
+    S: Sram<Error = Error<E>>,
+    E: Debug,
+{
+    const WRITE_SIZE: usize = 1;
+    const ERASE_SIZE: usize = 1;
+
+    async fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
+        self.erase_bytes(from, to).await
+    }
+
+    async fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.write_bytes(offset, bytes).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "sync"),
+    async(feature = "async")
+)]
+impl<S, E> embedded_storage::nor_flash::MultiwriteNorFlash for SramStorage<S>
+where
+    S: Sram<Error = Error<E>>,
+    E: Debug,
+{
+}
+}
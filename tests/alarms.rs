@@ -1,12 +1,13 @@
+#![cfg(feature = "sync")]
 use embedded_hal_mock::eh1::{i2c::Transaction as I2cTrans, spi::Transaction as SpiTrans};
 mod common;
 use self::common::{
-    destroy_ds3231, destroy_ds3232, destroy_ds3234, new_ds3231, new_ds3232, new_ds3234,
-    BitFlags as BF, Register, DEVICE_ADDRESS as DEV_ADDR,
+    destroy_ds3231, destroy_ds3232, destroy_ds3234, new_datetime, new_ds3231, new_ds3232,
+    new_ds3234, BitFlags as BF, Register, DEVICE_ADDRESS as DEV_ADDR,
 };
 use ds323x::{
-    Alarm1Matching as A1M, Alarm2Matching as A2M, DayAlarm1, DayAlarm2, Error, Hours, NaiveTime,
-    WeekdayAlarm1, WeekdayAlarm2,
+    Alarm1Config, Alarm1Matching as A1M, Alarm2Config, Alarm2Matching as A2M, DayAlarm1, DayAlarm2,
+    Error, Hours, NaiveTime, WeekdayAlarm1, WeekdayAlarm2,
 };
 
 #[macro_export]
@@ -1109,3 +1110,601 @@ mod alarm2_weekday {
         A2M::OncePerMinute
     );
 }
+
+macro_rules! get_invalid_alarm_state_test {
+    ($name:ident, $method:ident, $register:ident, [ $( $read_bin:expr ),+ ], [ $( $read_bin2:expr ),+ ]) => {
+        mod $name {
+            use super::*;
+            #[test]
+            fn cannot_get_ds3231() {
+                let trans = transactions_i2c_read!($register, [ $( $read_bin ),* ], [ ]);
+                let mut dev = new_ds3231(&trans);
+                match dev.$method() {
+                    Err(Error::InvalidDeviceState) => (),
+                    _ => panic!("InvalidDeviceState error not returned."),
+                }
+                destroy_ds3231(dev);
+            }
+            #[test]
+            fn cannot_get_ds3234() {
+                let trans = transactions_spi_read!($register, [ $( $read_bin ),* ], [ $( $read_bin2 ),* ]);
+                let mut dev = new_ds3234(&trans);
+                match dev.$method() {
+                    Err(Error::InvalidDeviceState) => (),
+                    _ => panic!("InvalidDeviceState error not returned."),
+                }
+                destroy_ds3234(dev);
+            }
+        }
+    };
+}
+
+mod alarm1_readback {
+    use super::*;
+    get_param_read_array_test!(
+        day_all_match,
+        alarm1,
+        Alarm1Config::Day(
+            DayAlarm1 {
+                day: 31,
+                hour: Hours::H24(23),
+                minute: 59,
+                second: 58
+            },
+            A1M::AllMatch
+        ),
+        ALARM1_SECONDS,
+        [0x58, 0x59, 0x23, 0x31],
+        [0, 0, 0, 0]
+    );
+    get_param_read_array_test!(
+        day_am,
+        alarm1,
+        Alarm1Config::Day(
+            DayAlarm1 {
+                day: 1,
+                hour: Hours::AM(2),
+                minute: 3,
+                second: 4
+            },
+            A1M::HoursMinutesAndSecondsMatch
+        ),
+        ALARM1_SECONDS,
+        [4, 3, 0b0100_0010, AM | 1],
+        [0, 0, 0, 0]
+    );
+    get_param_read_array_test!(
+        weekday_pm,
+        alarm1,
+        Alarm1Config::Weekday(
+            WeekdayAlarm1 {
+                weekday: 7,
+                hour: Hours::PM(12),
+                minute: 3,
+                second: 4
+            },
+            A1M::AllMatch
+        ),
+        ALARM1_SECONDS,
+        [4, 3, 0b0111_0010, BF::WEEKDAY | 7],
+        [0, 0, 0, 0]
+    );
+    get_param_read_array_test!(
+        weekday_ms,
+        alarm1,
+        Alarm1Config::Weekday(
+            WeekdayAlarm1 {
+                weekday: 1,
+                hour: Hours::H24(0),
+                minute: 3,
+                second: 4
+            },
+            A1M::MinutesAndSecondsMatch
+        ),
+        ALARM1_SECONDS,
+        [4, 3, AM, AM | BF::WEEKDAY | 1],
+        [0, 0, 0, 0]
+    );
+    get_param_read_array_test!(
+        day_s,
+        alarm1,
+        Alarm1Config::Day(
+            DayAlarm1 {
+                day: 1,
+                hour: Hours::H24(0),
+                minute: 0,
+                second: 4
+            },
+            A1M::SecondsMatch
+        ),
+        ALARM1_SECONDS,
+        [4, AM, AM, AM | 1],
+        [0, 0, 0, 0]
+    );
+    get_param_read_array_test!(
+        day_ops,
+        alarm1,
+        Alarm1Config::Day(
+            DayAlarm1 {
+                day: 1,
+                hour: Hours::H24(0),
+                minute: 0,
+                second: 0
+            },
+            A1M::OncePerSecond
+        ),
+        ALARM1_SECONDS,
+        [AM, AM, AM, AM | 1],
+        [0, 0, 0, 0]
+    );
+    get_invalid_alarm_state_test!(
+        invalid_mask,
+        alarm1,
+        ALARM1_SECONDS,
+        [AM, 0, AM, AM | 1],
+        [0, 0, 0, 0]
+    );
+}
+
+mod alarm2_readback {
+    use super::*;
+    get_param_read_array_test!(
+        day_all_match,
+        alarm2,
+        Alarm2Config::Day(
+            DayAlarm2 {
+                day: 31,
+                hour: Hours::H24(23),
+                minute: 59
+            },
+            A2M::AllMatch
+        ),
+        ALARM2_MINUTES,
+        [0x59, 0x23, 0x31],
+        [0, 0, 0]
+    );
+    get_param_read_array_test!(
+        weekday_hm_pm,
+        alarm2,
+        Alarm2Config::Weekday(
+            WeekdayAlarm2 {
+                weekday: 3,
+                hour: Hours::PM(11),
+                minute: 2
+            },
+            A2M::HoursAndMinutesMatch
+        ),
+        ALARM2_MINUTES,
+        [2, 0b0111_0001, AM | BF::WEEKDAY | 3],
+        [0, 0, 0]
+    );
+    get_param_read_array_test!(
+        day_m,
+        alarm2,
+        Alarm2Config::Day(
+            DayAlarm2 {
+                day: 1,
+                hour: Hours::H24(0),
+                minute: 3
+            },
+            A2M::MinutesMatch
+        ),
+        ALARM2_MINUTES,
+        [3, AM, AM | 1],
+        [0, 0, 0]
+    );
+    get_param_read_array_test!(
+        day_opm,
+        alarm2,
+        Alarm2Config::Day(
+            DayAlarm2 {
+                day: 1,
+                hour: Hours::H24(0),
+                minute: 0
+            },
+            A2M::OncePerMinute
+        ),
+        ALARM2_MINUTES,
+        [AM, AM, AM | 1],
+        [0, 0, 0]
+    );
+    get_invalid_alarm_state_test!(invalid_mask, alarm2, ALARM2_MINUTES, [0, AM, 1], [0, 0, 0]);
+}
+
+// Current date and time is 2021-03-31 09:00:00 (Wednesday)
+macro_rules! get_next_occurrence_test {
+    ($name:ident, $method:ident, $value:expr, $register:ident, [ $( $read_bin:expr ),+ ], [ $( $read_bin2:expr ),+ ]) => {
+        _get_param_test!(
+            $name,
+            $method,
+            $value,
+            [
+                transactions_i2c_read!($register, [ $( $read_bin ),* ], [ ]),
+                transactions_i2c_read!(SECONDS, [0, 0, 0x09, 4, 0x31, 0x03, 0x21], [ ])
+            ]
+            .concat(),
+            [
+                transactions_spi_read!($register, [ $( $read_bin ),* ], [ $( $read_bin2 ),* ]),
+                transactions_spi_read!(SECONDS, [0, 0, 0x09, 4, 0x31, 0x03, 0x21], [0, 0, 0, 0, 0, 0, 0])
+            ]
+            .concat()
+        );
+    };
+}
+
+mod next_occurrence {
+    use super::*;
+
+    get_next_occurrence_test!(
+        alarm1_day_31_skips_april,
+        next_alarm1_occurrence,
+        new_datetime(2021, 5, 31, 8, 0, 0),
+        ALARM1_SECONDS,
+        [0, 0, 0x08, 0x31],
+        [0, 0, 0, 0]
+    );
+    get_next_occurrence_test!(
+        alarm1_minutes_and_seconds,
+        next_alarm1_occurrence,
+        new_datetime(2021, 3, 31, 9, 30, 15),
+        ALARM1_SECONDS,
+        [0x15, 0x30, AM | 0x08, AM | 0x31],
+        [0, 0, 0, 0]
+    );
+    get_next_occurrence_test!(
+        alarm2_weekday,
+        next_alarm2_occurrence,
+        new_datetime(2021, 4, 2, 20, 15, 0),
+        ALARM2_MINUTES,
+        [0x15, 0x20, BF::WEEKDAY | 6],
+        [0, 0, 0]
+    );
+    get_next_occurrence_test!(
+        alarm2_once_per_minute,
+        next_alarm2_occurrence,
+        new_datetime(2021, 3, 31, 9, 1, 0),
+        ALARM2_MINUTES,
+        [AM, AM, AM],
+        [0, 0, 0]
+    );
+}
+
+// Current date and time is 2021-03-31 09:00:00 (Wednesday)
+macro_rules! set_alarm_at_test {
+    ($name:ident, $method:ident, $register:ident, [ $( $registers:expr ),+ ], $value:expr) => {
+        set_values_test!($name, $method,
+            [
+                transactions_i2c_read!(SECONDS, [0, 0, 0x09, 4, 0x31, 0x03, 0x21], [ ]).to_vec(),
+                vec![I2cTrans::write(DEV_ADDR, vec![Register::$register, $( $registers ),*])]
+            ]
+            .concat(),
+            [
+                transactions_spi_read!(SECONDS, [0, 0, 0x09, 4, 0x31, 0x03, 0x21], [0, 0, 0, 0, 0, 0, 0]).to_vec(),
+                vec![
+                    SpiTrans::transaction_start(),
+                    SpiTrans::write_vec(vec![Register::$register + 0x80, $( $registers ),*]),
+                    SpiTrans::transaction_end()
+                ]
+            ]
+            .concat(),
+            $value
+        );
+    };
+}
+
+macro_rules! set_alarm_at_too_far_test {
+    ($name:ident, $method:ident, $value:expr) => {
+        mod $name {
+            use super::*;
+            #[test]
+            fn cannot_set_ds3231() {
+                let trans = transactions_i2c_read!(SECONDS, [0, 0, 0x09, 4, 0x31, 0x03, 0x21], []);
+                let mut dev = new_ds3231(&trans);
+                assert_invalid_input_data!(dev.$method($value));
+                destroy_ds3231(dev);
+            }
+            #[test]
+            fn cannot_set_ds3234() {
+                let trans = transactions_spi_read!(
+                    SECONDS,
+                    [0, 0, 0x09, 4, 0x31, 0x03, 0x21],
+                    [0, 0, 0, 0, 0, 0, 0]
+                );
+                let mut dev = new_ds3234(&trans);
+                assert_invalid_input_data!(dev.$method($value));
+                destroy_ds3234(dev);
+            }
+        }
+    };
+}
+
+mod alarm_at {
+    use super::*;
+
+    set_alarm_at_test!(
+        alarm1_same_day,
+        set_alarm1_at,
+        ALARM1_SECONDS,
+        [0x30, 0x20, 0x10, 0x31],
+        new_datetime(2021, 3, 31, 10, 20, 30)
+    );
+    set_alarm_at_test!(
+        alarm1_next_month,
+        set_alarm1_at,
+        ALARM1_SECONDS,
+        [0x30, 0x20, 0x10, 0x15],
+        new_datetime(2021, 4, 15, 10, 20, 30)
+    );
+    set_alarm_at_too_far_test!(
+        alarm1_too_far,
+        set_alarm1_at,
+        new_datetime(2021, 5, 15, 10, 20, 30)
+    );
+    set_alarm_at_too_far_test!(
+        alarm1_now,
+        set_alarm1_at,
+        new_datetime(2021, 3, 31, 9, 0, 0)
+    );
+    set_alarm_at_too_far_test!(
+        alarm1_past,
+        set_alarm1_at,
+        new_datetime(2021, 3, 30, 9, 0, 0)
+    );
+    set_alarm_at_test!(
+        alarm2_next_minute,
+        set_alarm2_at,
+        ALARM2_MINUTES,
+        [0x01, 0x09, 0x31],
+        new_datetime(2021, 3, 31, 9, 1, 0)
+    );
+    set_alarm_at_too_far_test!(
+        alarm2_too_far,
+        set_alarm2_at,
+        new_datetime(2021, 5, 1, 9, 0, 0)
+    );
+    set_alarm_at_too_far_test!(
+        alarm2_with_seconds,
+        set_alarm2_at,
+        new_datetime(2021, 3, 31, 9, 1, 1)
+    );
+    set_alarm_at_test!(
+        alarm2_rounded,
+        set_alarm2_at_rounded,
+        ALARM2_MINUTES,
+        [0x02, 0x09, 0x31],
+        new_datetime(2021, 3, 31, 9, 1, 1)
+    );
+
+    #[test]
+    fn alarm2_rounded_returns_programmed_time() {
+        let trans = [
+            transactions_i2c_read!(SECONDS, [0, 0, 0x09, 4, 0x31, 0x03, 0x21], []).to_vec(),
+            vec![I2cTrans::write(
+                DEV_ADDR,
+                vec![Register::ALARM2_MINUTES, 0x00, 0x10, 0x31],
+            )],
+        ]
+        .concat();
+        let mut dev = new_ds3231(&trans);
+        assert_eq!(
+            new_datetime(2021, 3, 31, 10, 0, 0),
+            dev.set_alarm2_at_rounded(new_datetime(2021, 3, 31, 9, 59, 30))
+                .unwrap()
+        );
+        destroy_ds3231(dev);
+    }
+}
+
+macro_rules! _set_alarm_in_test {
+    ($name:ident, $method:ident, $create_method:ident, $destroy_method:ident, $transactions:expr, $interval:expr, $expected:expr) => {
+        #[test]
+        fn $name() {
+            let trans = $transactions;
+            let mut dev = $create_method(&trans);
+            assert_eq!($expected, dev.$method($interval).unwrap());
+            $destroy_method(dev);
+        }
+    };
+}
+
+// Current date and time is 2021-03-31 09:00:00 (Wednesday) and 09:00:01 after setting the alarm
+macro_rules! set_alarm_in_test {
+    ($name:ident, $method:ident, $register:ident, [ $( $registers:expr ),+ ], $interval:expr, $expected:expr) => {
+        mod $name {
+            use super::*;
+            _set_alarm_in_test!(
+                can_set_ds3231,
+                $method,
+                new_ds3231,
+                destroy_ds3231,
+                [
+                    transactions_i2c_read!(SECONDS, [0, 0, 0x09, 4, 0x31, 0x03, 0x21], [ ]).to_vec(),
+                    vec![I2cTrans::write(DEV_ADDR, vec![Register::$register, $( $registers ),*])],
+                    transactions_i2c_read!(SECONDS, [1, 0, 0x09, 4, 0x31, 0x03, 0x21], [ ]).to_vec(),
+                ]
+                .concat(),
+                $interval,
+                $expected
+            );
+            _set_alarm_in_test!(
+                can_set_ds3232,
+                $method,
+                new_ds3232,
+                destroy_ds3232,
+                [
+                    transactions_i2c_read!(SECONDS, [0, 0, 0x09, 4, 0x31, 0x03, 0x21], [ ]).to_vec(),
+                    vec![I2cTrans::write(DEV_ADDR, vec![Register::$register, $( $registers ),*])],
+                    transactions_i2c_read!(SECONDS, [1, 0, 0x09, 4, 0x31, 0x03, 0x21], [ ]).to_vec(),
+                ]
+                .concat(),
+                $interval,
+                $expected
+            );
+            _set_alarm_in_test!(
+                can_set_ds3234,
+                $method,
+                new_ds3234,
+                destroy_ds3234,
+                [
+                    transactions_spi_read!(SECONDS, [0, 0, 0x09, 4, 0x31, 0x03, 0x21], [0, 0, 0, 0, 0, 0, 0]).to_vec(),
+                    vec![
+                        SpiTrans::transaction_start(),
+                        SpiTrans::write_vec(vec![Register::$register + 0x80, $( $registers ),*]),
+                        SpiTrans::transaction_end()
+                    ],
+                    transactions_spi_read!(SECONDS, [1, 0, 0x09, 4, 0x31, 0x03, 0x21], [0, 0, 0, 0, 0, 0, 0]).to_vec(),
+                ]
+                .concat(),
+                $interval,
+                $expected
+            );
+        }
+    };
+}
+
+mod alarm_in {
+    use super::*;
+    use core::time::Duration;
+
+    set_alarm_in_test!(
+        alarm1_seconds,
+        set_alarm1_in,
+        ALARM1_SECONDS,
+        [0x30, 0x01, 0x09, 0x31],
+        Duration::from_secs(90),
+        new_datetime(2021, 3, 31, 9, 1, 30)
+    );
+    set_alarm_in_test!(
+        alarm1_rounds_up_fraction,
+        set_alarm1_in,
+        ALARM1_SECONDS,
+        [0x02, 0, 0x09, 0x31],
+        Duration::from_millis(1500),
+        new_datetime(2021, 3, 31, 9, 0, 2)
+    );
+    set_alarm_in_test!(
+        alarm1_next_month,
+        set_alarm1_in,
+        ALARM1_SECONDS,
+        [0, 0, 0, 0x01],
+        Duration::from_secs(15 * 3600),
+        new_datetime(2021, 4, 1, 0, 0, 0)
+    );
+    set_alarm_in_test!(
+        alarm2_rounds_up_to_minute,
+        set_alarm2_in,
+        ALARM2_MINUTES,
+        [0x02, 0x09, 0x31],
+        Duration::from_secs(90),
+        new_datetime(2021, 3, 31, 9, 2, 0)
+    );
+    set_alarm_at_too_far_test!(alarm1_zero, set_alarm1_in, Duration::from_secs(0));
+    set_alarm_at_too_far_test!(
+        alarm1_too_long,
+        set_alarm1_in,
+        Duration::from_secs(40 * 86_400)
+    );
+    set_alarm_at_too_far_test!(
+        alarm2_too_long,
+        set_alarm2_in,
+        Duration::from_secs(40 * 86_400)
+    );
+
+    #[test]
+    fn alarm1_missed() {
+        let trans = [
+            transactions_i2c_read!(SECONDS, [0, 0, 0x09, 4, 0x31, 0x03, 0x21], []).to_vec(),
+            vec![I2cTrans::write(
+                DEV_ADDR,
+                vec![Register::ALARM1_SECONDS, 0x01, 0, 0x09, 0x31],
+            )],
+            transactions_i2c_read!(SECONDS, [1, 0, 0x09, 4, 0x31, 0x03, 0x21], []).to_vec(),
+        ]
+        .concat();
+        let mut dev = new_ds3231(&trans);
+        match dev.set_alarm1_in(Duration::from_secs(1)) {
+            Err(Error::AlarmMissed) => (),
+            _ => panic!("AlarmMissed error not returned."),
+        }
+        destroy_ds3231(dev);
+    }
+
+    #[test]
+    fn alarm2_missed_at_target_minute() {
+        let trans = [
+            transactions_i2c_read!(SECONDS, [0, 0, 0x09, 4, 0x31, 0x03, 0x21], []).to_vec(),
+            vec![I2cTrans::write(
+                DEV_ADDR,
+                vec![Register::ALARM2_MINUTES, 0x02, 0x09, 0x31],
+            )],
+            // the device only matches when the time is updated to the target
+            transactions_i2c_read!(SECONDS, [0, 0x02, 0x09, 4, 0x31, 0x03, 0x21], []).to_vec(),
+        ]
+        .concat();
+        let mut dev = new_ds3231(&trans);
+        match dev.set_alarm2_in(Duration::from_secs(90)) {
+            Err(Error::AlarmMissed) => (),
+            _ => panic!("AlarmMissed error not returned."),
+        }
+        destroy_ds3231(dev);
+    }
+}
+
+mod alarm_schedule {
+    use super::*;
+    use ds323x::Schedule;
+
+    fn schedule(expr: &str) -> Schedule {
+        expr.parse().unwrap()
+    }
+
+    set_alarm_in_test!(
+        alarm1_every_15_minutes,
+        set_alarm1_schedule,
+        ALARM1_SECONDS,
+        [0, 0x15, 0x09, 0x31],
+        &schedule("*/15 8-17 * * mon-fri"),
+        new_datetime(2021, 3, 31, 9, 15, 0)
+    );
+    set_alarm_in_test!(
+        alarm1_with_seconds_next_month,
+        set_alarm1_schedule,
+        ALARM1_SECONDS,
+        [0x30, 0, 0x08, 0x01],
+        &schedule("30 0 8 1 * *"),
+        new_datetime(2021, 4, 1, 8, 0, 30)
+    );
+    set_alarm_in_test!(
+        alarm2_every_15_minutes,
+        set_alarm2_schedule,
+        ALARM2_MINUTES,
+        [0x15, 0x09, 0x31],
+        &schedule("*/15 8-17 * * mon-fri"),
+        new_datetime(2021, 3, 31, 9, 15, 0)
+    );
+    set_invalid_test!(
+        alarm2_with_seconds,
+        set_alarm2_schedule,
+        new_ds3231,
+        destroy_ds3231,
+        &schedule("30 * * * * *")
+    );
+    set_alarm_at_too_far_test!(alarm1_never, set_alarm1_schedule, &schedule("0 0 31 2 *"));
+    // The alarm matches earlier, today, on the same day of the month and time
+    set_alarm_in_test!(
+        alarm1_next_month_with_day,
+        set_alarm1_schedule,
+        ALARM1_SECONDS,
+        [0, 0, 0x12, 0x31],
+        &schedule("0 12 31 may *"),
+        new_datetime(2021, 5, 31, 12, 0, 0)
+    );
+    set_alarm_in_test!(
+        alarm2_yearly,
+        set_alarm2_schedule,
+        ALARM2_MINUTES,
+        [0, 0, 0x01],
+        &schedule("0 0 1 jan *"),
+        new_datetime(2022, 1, 1, 0, 0, 0)
+    );
+}
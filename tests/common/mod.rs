@@ -1,4 +1,4 @@
-use ds323x::{ic, interface, Ds323x};
+use ds323x::{ic, interface, Ds323x, NaiveDate, NaiveDateTime};
 use embedded_hal_mock::eh1::{
     i2c::{Mock as I2cMock, Transaction as I2cTrans},
     spi::{Mock as SpiMock, Transaction as SpiTrans},
@@ -30,6 +30,9 @@ impl Register {
     pub const AGING_OFFSET: u8 = 0x10;
     pub const TEMP_MSB: u8 = 0x11;
     pub const TEMP_CONV: u8 = 0x13;
+    pub const SRAM_START: u8 = 0x14;
+    pub const SRAM_ADDRESS: u8 = 0x18;
+    pub const SRAM_DATA: u8 = 0x19;
 }
 
 pub struct BitFlags;
@@ -75,6 +78,49 @@ pub fn new_ds3234(
     Ds323x::new_ds3234(SpiMock::new(transactions))
 }
 
+// Polls a future to completion. The mocks never return `Poll::Pending`.
+#[cfg(feature = "async")]
+#[allow(unused)]
+pub fn block_on<F: core::future::Future>(future: F) -> F::Output {
+    let mut future = core::pin::pin!(future);
+    loop {
+        if let Some(output) = poll_once(future.as_mut()) {
+            return output;
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+#[allow(unused)]
+pub fn poll_once<F: core::future::Future>(future: core::pin::Pin<&mut F>) -> Option<F::Output> {
+    use std::{
+        sync::Arc,
+        task::{Context, Poll, Wake, Waker},
+    };
+    struct NoopWaker;
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+    let waker = Waker::from(Arc::new(NoopWaker));
+    match future.poll(&mut Context::from_waker(&waker)) {
+        Poll::Ready(output) => Some(output),
+        Poll::Pending => None,
+    }
+}
+
+#[allow(unused)]
+pub fn new_datetime(y: i32, mo: u32, d: u32, h: u32, min: u32, s: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(y, mo, d)
+        .unwrap()
+        .and_hms_opt(h, min, s)
+        .unwrap()
+}
+
+#[allow(unused)]
+pub fn read_status(status: u8) -> I2cTrans {
+    I2cTrans::write_read(DEVICE_ADDRESS, vec![Register::STATUS], vec![status])
+}
+
 pub fn destroy_ds3231(dev: Ds323x<interface::I2cInterface<I2cMock>, ic::DS3231>) {
     dev.destroy_ds3231().done();
 }
@@ -1,4 +1,7 @@
-use ds323x::SqWFreq;
+#![cfg(feature = "sync")]
+use ds323x::{
+    Config, Control, Ds3234ExtendedConfig, ExtendedConfig, IntSqwOutput, SqWFreq, TempConvRate,
+};
 use embedded_hal_mock::eh1::{i2c::Transaction as I2cTrans, spi::Transaction as SpiTrans};
 
 mod common;
@@ -291,3 +294,321 @@ set_param_test!(
     SqWFreq::_8_192Hz,
     CONTROL_POR_VALUE | BF::RS2 | BF::RS1
 );
+
+const POR_CONTROL: Control = Control {
+    oscillator_enabled: true,
+    square_wave_on_battery: false,
+    convert_temperature: false,
+    square_wave_frequency: SqWFreq::_8_192Hz,
+    int_sqw_output: IntSqwOutput::Interrupt,
+    alarm2_interrupts_enabled: false,
+    alarm1_interrupts_enabled: false,
+};
+
+const ALL_SET_CONTROL: Control = Control {
+    oscillator_enabled: false,
+    square_wave_on_battery: true,
+    convert_temperature: true,
+    square_wave_frequency: SqWFreq::_8_192Hz,
+    int_sqw_output: IntSqwOutput::Interrupt,
+    alarm2_interrupts_enabled: true,
+    alarm1_interrupts_enabled: true,
+};
+
+const SQW_1_024_CONTROL: Control = Control {
+    square_wave_frequency: SqWFreq::_1_024Hz,
+    int_sqw_output: IntSqwOutput::SquareWave,
+    ..POR_CONTROL
+};
+
+get_param_test!(
+    get_control_por,
+    control,
+    CONTROL,
+    POR_CONTROL,
+    CONTROL_POR_VALUE
+);
+get_param_test!(get_control_all_set, control, CONTROL, ALL_SET_CONTROL, 0xFF);
+get_param_test!(
+    get_control_sqw_1_024,
+    control,
+    CONTROL,
+    SQW_1_024_CONTROL,
+    BF::RS1
+);
+
+set_param_test!(
+    set_control_por,
+    set_control,
+    CONTROL,
+    POR_CONTROL,
+    CONTROL_POR_VALUE
+);
+set_param_test!(
+    set_control_all_set,
+    set_control,
+    CONTROL,
+    ALL_SET_CONTROL,
+    0xFF
+);
+set_param_test!(
+    set_control_sqw_1_024,
+    set_control,
+    CONTROL,
+    SQW_1_024_CONTROL,
+    BF::RS1
+);
+
+#[test]
+fn set_control_updates_cache() {
+    let mut dev = new_ds3231(&[
+        I2cTrans::write(DEV_ADDR, vec![Register::CONTROL, BF::RS1]),
+        I2cTrans::write(DEV_ADDR, vec![Register::CONTROL, BF::RS1 | BF::BBSQW]),
+    ]);
+    dev.set_control(SQW_1_024_CONTROL).unwrap();
+    dev.enable_square_wave().unwrap();
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn set_control_does_not_repeat_conversion() {
+    let mut dev = new_ds3231(&[
+        I2cTrans::write(DEV_ADDR, vec![Register::CONTROL, 0xFF]),
+        I2cTrans::write(DEV_ADDR, vec![Register::CONTROL, !BF::TEMP_CONV]),
+    ]);
+    dev.set_control(ALL_SET_CONTROL).unwrap();
+    dev.enable_square_wave().unwrap();
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn sync_from_device_updates_cache() {
+    let mut dev = new_ds3231(&[
+        I2cTrans::write_read(
+            DEV_ADDR,
+            vec![Register::CONTROL],
+            vec![
+                BF::EOSC | BF::TEMP_CONV | BF::RS1,
+                BF::CRATE1 | BF::EN32KHZ | BF::BUSY | BF::ALARM1F,
+            ],
+        ),
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![Register::CONTROL, BF::EOSC | BF::RS1 | BF::BBSQW],
+        ),
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![Register::STATUS, BF::CRATE1 | BF::ALARM2F | BF::ALARM1F],
+        ),
+    ]);
+    dev.sync_from_device().unwrap();
+    dev.enable_square_wave().unwrap();
+    dev.disable_32khz_output().unwrap();
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn sync_from_device_reads_registers_at_once_ds3234() {
+    let mut dev = new_ds3234(&[
+        SpiTrans::transaction_start(),
+        SpiTrans::transfer_in_place(
+            vec![Register::CONTROL, 0, 0],
+            vec![
+                Register::CONTROL,
+                BF::EOSC | BF::RS1,
+                BF::CRATE1 | BF::ALARM2F,
+            ],
+        ),
+        SpiTrans::transaction_end(),
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec(vec![
+            Register::CONTROL + 0x80,
+            BF::EOSC | BF::RS1 | BF::BBSQW,
+        ]),
+        SpiTrans::transaction_end(),
+    ]);
+    dev.sync_from_device().unwrap();
+    dev.enable_square_wave().unwrap();
+    destroy_ds3234(dev);
+}
+
+const CONFIG: Config = Config {
+    control: SQW_1_024_CONTROL,
+    enabled_32khz_output: false,
+    aging_offset: -1,
+    extended: (),
+};
+
+const EXTENDED_CONFIG: Config<ExtendedConfig> = Config {
+    control: SQW_1_024_CONTROL,
+    enabled_32khz_output: true,
+    aging_offset: 127,
+    extended: ExtendedConfig {
+        enabled_32khz_output_on_battery: true,
+        temperature_conversion_rate: TempConvRate::_256s,
+    },
+};
+
+const DS3234_CONFIG: Config<Ds3234ExtendedConfig> = Config {
+    control: SQW_1_024_CONTROL,
+    enabled_32khz_output: true,
+    aging_offset: 127,
+    extended: Ds3234ExtendedConfig {
+        enabled_32khz_output_on_battery: true,
+        temperature_conversion_rate: TempConvRate::_256s,
+        enabled_temperature_conversions_on_battery: true,
+    },
+};
+
+const DS3234_CONFIG_WITHOUT_CONVERSIONS_ON_BATTERY: Config<Ds3234ExtendedConfig> = Config {
+    extended: Ds3234ExtendedConfig {
+        enabled_temperature_conversions_on_battery: false,
+        ..DS3234_CONFIG.extended
+    },
+    ..DS3234_CONFIG
+};
+
+const EXTENDED_CONFIG_STATUS: u8 = BF::BB32KHZ | BF::CRATE1 | BF::EN32KHZ;
+
+#[test]
+fn can_apply_config_ds3231() {
+    let mut dev = new_ds3231(&[
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![
+                Register::CONTROL,
+                BF::RS1,
+                BF::OSC_STOP | BF::ALARM2F | BF::ALARM1F,
+                0xFF,
+            ],
+        ),
+        I2cTrans::write(DEV_ADDR, vec![Register::CONTROL, BF::RS1 | BF::BBSQW]),
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![
+                Register::STATUS,
+                BF::OSC_STOP | BF::EN32KHZ | BF::ALARM2F | BF::ALARM1F,
+            ],
+        ),
+    ]);
+    dev.apply_config(&CONFIG).unwrap();
+    dev.enable_square_wave().unwrap();
+    dev.enable_32khz_output().unwrap();
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn can_apply_config_ds3232() {
+    let mut dev = new_ds3232(&[I2cTrans::write(
+        DEV_ADDR,
+        vec![
+            Register::CONTROL,
+            BF::RS1,
+            BF::OSC_STOP | EXTENDED_CONFIG_STATUS | BF::ALARM2F | BF::ALARM1F,
+            127,
+        ],
+    )]);
+    dev.apply_config(&EXTENDED_CONFIG).unwrap();
+    destroy_ds3232(dev);
+}
+
+#[test]
+fn can_apply_config_ds3234() {
+    let mut dev = new_ds3234(&[
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec(vec![
+            Register::CONTROL + 0x80,
+            BF::RS1,
+            BF::OSC_STOP | EXTENDED_CONFIG_STATUS | BF::ALARM2F | BF::ALARM1F,
+            127,
+        ]),
+        SpiTrans::transaction_end(),
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec(vec![Register::TEMP_CONV + 0x80, 0]),
+        SpiTrans::transaction_end(),
+    ]);
+    dev.apply_config(&DS3234_CONFIG).unwrap();
+    destroy_ds3234(dev);
+}
+
+#[test]
+fn can_apply_config_ds3234_without_conversions_on_battery() {
+    let mut dev = new_ds3234(&[
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec(vec![
+            Register::CONTROL + 0x80,
+            BF::RS1,
+            BF::OSC_STOP | EXTENDED_CONFIG_STATUS | BF::ALARM2F | BF::ALARM1F,
+            127,
+        ]),
+        SpiTrans::transaction_end(),
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec(vec![Register::TEMP_CONV + 0x80, BF::TEMP_CONV_BAT]),
+        SpiTrans::transaction_end(),
+    ]);
+    dev.apply_config(&DS3234_CONFIG_WITHOUT_CONVERSIONS_ON_BATTERY)
+        .unwrap();
+    destroy_ds3234(dev);
+}
+
+#[test]
+fn can_read_config_ds3231() {
+    let mut dev = new_ds3231(&[I2cTrans::write_read(
+        DEV_ADDR,
+        vec![Register::CONTROL],
+        vec![BF::RS1, BF::OSC_STOP | BF::BUSY | BF::ALARM1F, 0xFF],
+    )]);
+    assert_eq!(CONFIG, dev.read_config().unwrap());
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn can_read_config_ds3232() {
+    let mut dev = new_ds3232(&[I2cTrans::write_read(
+        DEV_ADDR,
+        vec![Register::CONTROL],
+        vec![BF::RS1, EXTENDED_CONFIG_STATUS | BF::ALARM2F, 127],
+    )]);
+    assert_eq!(EXTENDED_CONFIG, dev.read_config().unwrap());
+    destroy_ds3232(dev);
+}
+
+#[test]
+fn can_read_config_ds3234() {
+    let mut dev = new_ds3234(&[
+        SpiTrans::transaction_start(),
+        SpiTrans::transfer_in_place(
+            vec![Register::CONTROL, 0, 0, 0],
+            vec![Register::CONTROL, BF::RS1, EXTENDED_CONFIG_STATUS, 127],
+        ),
+        SpiTrans::transaction_end(),
+        SpiTrans::transaction_start(),
+        SpiTrans::transfer_in_place(vec![Register::TEMP_CONV, 0], vec![Register::TEMP_CONV, 0]),
+        SpiTrans::transaction_end(),
+    ]);
+    assert_eq!(DS3234_CONFIG, dev.read_config().unwrap());
+    destroy_ds3234(dev);
+}
+
+#[test]
+fn can_read_config_ds3234_without_conversions_on_battery() {
+    let mut dev = new_ds3234(&[
+        SpiTrans::transaction_start(),
+        SpiTrans::transfer_in_place(
+            vec![Register::CONTROL, 0, 0, 0],
+            vec![Register::CONTROL, BF::RS1, EXTENDED_CONFIG_STATUS, 127],
+        ),
+        SpiTrans::transaction_end(),
+        SpiTrans::transaction_start(),
+        SpiTrans::transfer_in_place(
+            vec![Register::TEMP_CONV, 0],
+            vec![Register::TEMP_CONV, BF::TEMP_CONV_BAT],
+        ),
+        SpiTrans::transaction_end(),
+    ]);
+    assert_eq!(
+        DS3234_CONFIG_WITHOUT_CONVERSIONS_ON_BATTERY,
+        dev.read_config().unwrap()
+    );
+    destroy_ds3234(dev);
+}
@@ -1,3 +1,4 @@
+#![cfg(feature = "sync")]
 mod common;
 use self::common::{
     destroy_ds3231, destroy_ds3232, destroy_ds3234, new_ds3231, new_ds3232, new_ds3234,
@@ -16,3 +17,13 @@ macro_rules! construction_test {
 construction_test!(can_create_ds3231, new_ds3231, destroy_ds3231);
 construction_test!(can_create_ds3232, new_ds3232, destroy_ds3232);
 construction_test!(can_create_ds3234, new_ds3234, destroy_ds3234);
+
+#[cfg(all(feature = "sync", feature = "async"))]
+#[test]
+fn can_create_blocking_and_async_drivers_together() {
+    use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+    let blocking = ds323x::blocking::Ds323x::new_ds3231(I2cMock::new(&[]));
+    let asynch = ds323x::asynch::Ds323x::new_ds3231(I2cMock::new(&[]));
+    blocking.destroy_ds3231().done();
+    asynch.destroy_ds3231().done();
+}
@@ -1,21 +1,14 @@
+#![cfg(feature = "sync")]
 use embedded_hal_mock::eh1::{i2c::Transaction as I2cTrans, spi::Transaction as SpiTrans};
-use rtcc::NaiveDateTime;
 mod common;
 use self::common::{
-    destroy_ds3231, destroy_ds3232, destroy_ds3234, new_ds3231, new_ds3232, new_ds3234, Register,
-    DEVICE_ADDRESS as DEV_ADDR,
+    destroy_ds3231, destroy_ds3232, destroy_ds3234, new_datetime, new_ds3231, new_ds3232,
+    new_ds3234, Register, DEVICE_ADDRESS as DEV_ADDR,
 };
 #[allow(unused)] // Rust 1.31.0 is confused due to the macros
 use ds323x::Rtcc;
 use ds323x::{DateTimeAccess, Error, Hours, NaiveDate, NaiveTime};
 
-fn new_datetime(y: i32, mo: u32, d: u32, h: u32, min: u32, s: u32) -> NaiveDateTime {
-    NaiveDate::from_ymd_opt(y, mo, d)
-        .unwrap()
-        .and_hms_opt(h, min, s)
-        .unwrap()
-}
-
 fn new_date(y: i32, mo: u32, d: u32) -> NaiveDate {
     NaiveDate::from_ymd_opt(y, mo, d).unwrap()
 }
@@ -403,3 +396,39 @@ mod datetime {
 
     for_all!(invalid_dt_test);
 }
+
+#[cfg(feature = "async")]
+mod async_rtcc {
+    use super::*;
+    use crate::common::block_on;
+    use ds323x::{asynch::Ds323x, AsyncDateTimeAccess, AsyncRtcc};
+    use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+
+    // Generic code written against the async traits and the `rtcc` types
+    async fn read_hours<R: AsyncRtcc>(rtc: &mut R) -> Result<rtcc::Hours, R::Error> {
+        rtc.hours().await
+    }
+
+    #[test]
+    fn can_read_hours() {
+        let mut dev = Ds323x::new_ds3231(I2cMock::new(&[I2cTrans::write_read(
+            DEV_ADDR,
+            vec![Register::HOURS],
+            vec![0b0010_0001],
+        )]));
+        assert_eq!(Hours::H24(21), block_on(read_hours(&mut dev)).unwrap());
+        dev.destroy_ds3231().done();
+    }
+
+    #[test]
+    fn can_read_datetime() {
+        let mut dev = Ds323x::new_ds3231(I2cMock::new(&[I2cTrans::write_read(
+            DEV_ADDR,
+            vec![Register::SECONDS],
+            vec![0x58, 0x59, 0x23, 0x04, 0x31, 0x03, 0x21],
+        )]));
+        let datetime = block_on(dev.datetime()).unwrap();
+        assert_eq!(new_datetime(2021, 3, 31, 23, 59, 58), datetime);
+        dev.destroy_ds3231().done();
+    }
+}
@@ -1,3 +1,4 @@
+#![cfg(feature = "sync")]
 use ds323x::TempConvRate;
 use embedded_hal_mock::eh1::{i2c::Transaction as I2cTrans, spi::Transaction as SpiTrans};
 
@@ -1,3 +1,4 @@
+#![cfg(feature = "sync")]
 use embedded_hal_mock::eh1::spi::Transaction as SpiTrans;
 
 #[allow(unused)]
@@ -0,0 +1,162 @@
+#![cfg(feature = "sync")]
+use embedded_hal_mock::eh1::{i2c::Transaction as I2cTrans, spi::Transaction as SpiTrans};
+#[allow(unused)]
+mod common;
+use self::common::{
+    destroy_ds3231, destroy_ds3232, destroy_ds3234, new_ds3231, new_ds3232, new_ds3234,
+    BitFlags as BF, Register, CONTROL_POR_VALUE, DEVICE_ADDRESS as DEV_ADDR,
+};
+use ds323x::RegisterDump;
+
+fn new_dump(temperature_conversion: Option<u8>, sram: Option<(u8, u8)>) -> RegisterDump {
+    RegisterDump {
+        datetime: [0x58, 0x59, 0x23, 0x04, 0x31, 0x03, 0x21],
+        alarm1: [0x00, 0x30, 0x07, 0x80],
+        alarm2: [0x15, 0x06, 0x81],
+        control: BF::TEMP_CONV | BF::INTCN | BF::ALARM1_INT_EN,
+        status: BF::OSC_STOP | BF::CRATE1 | BF::BUSY | BF::ALARM1F,
+        aging_offset: 0xFE,
+        temperature: [0x19, 0x40],
+        temperature_conversion,
+        sram_address: sram.map(|(address, _)| address),
+        sram_data: sram.map(|(_, data)| data),
+    }
+}
+
+// Values of the first `len` registers
+fn registers(len: usize) -> Vec<u8> {
+    let dump = new_dump(Some(BF::TEMP_CONV_BAT), Some((0x20, 0xAB)));
+    let mut data = dump.datetime.to_vec();
+    data.extend_from_slice(&dump.alarm1);
+    data.extend_from_slice(&dump.alarm2);
+    data.extend_from_slice(&[dump.control, dump.status, dump.aging_offset]);
+    data.extend_from_slice(&dump.temperature);
+    data.extend_from_slice(&[BF::TEMP_CONV_BAT, 0, 0, 0, 0, 0x20, 0xAB]);
+    data.truncate(len);
+    data
+}
+
+#[test]
+fn can_dump_registers_ds3231() {
+    let mut dev = new_ds3231(&[I2cTrans::write_read(
+        DEV_ADDR,
+        vec![Register::SECONDS],
+        registers(0x13),
+    )]);
+    assert_eq!(new_dump(None, None), dev.dump_registers().unwrap());
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn can_dump_registers_ds3232() {
+    let mut dev = new_ds3232(&[I2cTrans::write_read(
+        DEV_ADDR,
+        vec![Register::SECONDS],
+        registers(0x14),
+    )]);
+    let dump = new_dump(Some(BF::TEMP_CONV_BAT), None);
+    assert_eq!(dump, dev.dump_registers().unwrap());
+    destroy_ds3232(dev);
+}
+
+#[test]
+fn can_dump_registers_ds3234() {
+    let mut dev = new_ds3234(&[
+        SpiTrans::transaction_start(),
+        SpiTrans::transfer_in_place(vec![Register::SECONDS; 0x1B], {
+            let mut payload = vec![Register::SECONDS];
+            payload.extend(registers(0x1A));
+            payload
+        }),
+        SpiTrans::transaction_end(),
+    ]);
+    let dump = new_dump(Some(BF::TEMP_CONV_BAT), Some((0x20, 0xAB)));
+    assert_eq!(dump, dev.dump_registers().unwrap());
+    destroy_ds3234(dev);
+}
+
+// Alarm, control, status and aging offset registers written back
+fn restore_payload(status: u8) -> Vec<u8> {
+    vec![
+        Register::ALARM1_SECONDS,
+        0x00,
+        0x30,
+        0x07,
+        0x80,
+        0x15,
+        0x06,
+        0x81,
+        BF::INTCN | BF::ALARM1_INT_EN,
+        status | BF::ALARM2F | BF::ALARM1F,
+        0xFE,
+    ]
+}
+
+#[test]
+fn can_restore_registers_ds3231() {
+    let mut dev = new_ds3231(&[
+        I2cTrans::write(DEV_ADDR, restore_payload(BF::OSC_STOP | BF::CRATE1)),
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![Register::CONTROL, BF::INTCN | BF::ALARM1_INT_EN | BF::BBSQW],
+        ),
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![
+                Register::STATUS,
+                BF::OSC_STOP | BF::CRATE1 | BF::EN32KHZ | BF::ALARM2F | BF::ALARM1F,
+            ],
+        ),
+    ]);
+    dev.restore_registers(&new_dump(None, None)).unwrap();
+    dev.enable_square_wave().unwrap();
+    dev.enable_32khz_output().unwrap();
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn restore_registers_keeps_oscillator_stop_flag_cleared() {
+    let mut dev = new_ds3232(&[
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![
+                Register::STATUS,
+                BF::BB32KHZ | BF::EN32KHZ | BF::ALARM2F | BF::ALARM1F,
+            ],
+        ),
+        I2cTrans::write(DEV_ADDR, restore_payload(BF::CRATE1)),
+    ]);
+    dev.clear_has_been_stopped_flag().unwrap();
+    dev.restore_registers(&new_dump(Some(0), None)).unwrap();
+    destroy_ds3232(dev);
+}
+
+#[test]
+fn can_restore_registers_ds3234() {
+    let mut dev = new_ds3234(&[
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec({
+            let mut payload = restore_payload(BF::OSC_STOP | BF::CRATE1);
+            payload[0] += 0x80;
+            payload
+        }),
+        SpiTrans::transaction_end(),
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec(vec![Register::TEMP_CONV + 0x80, BF::TEMP_CONV_BAT]),
+        SpiTrans::transaction_end(),
+    ]);
+    let dump = new_dump(Some(0xFF), Some((0x20, 0xAB)));
+    dev.restore_registers(&dump).unwrap();
+    destroy_ds3234(dev);
+}
+
+#[test]
+fn debug_prints_hexadecimal_values() {
+    let mut dump = new_dump(None, None);
+    dump.control = CONTROL_POR_VALUE;
+    let output = format!("{:?}", dump);
+    assert!(output.contains("datetime: [58, 59, 23, 04, 31, 03, 21]"));
+    assert!(output.contains("control: 0b00011100"));
+    assert!(output.contains("aging_offset: 0xfe"));
+    assert!(output.contains("temperature_conversion: None"));
+}
@@ -0,0 +1,111 @@
+#![cfg(feature = "sync")]
+use ds323x::{
+    interface::{ReadData, WriteData},
+    DateTimeAccess, Ds323x, Error, NaiveDate, Sram,
+};
+
+// Register file of a DS3232 kept in memory
+struct Registers {
+    data: [u8; 0x100],
+    fail: bool,
+}
+
+impl Registers {
+    fn new() -> Self {
+        Registers {
+            data: [0; 0x100],
+            fail: false,
+        }
+    }
+
+    fn check(&self) -> Result<(), Error<&'static str>> {
+        if self.fail {
+            Err(Error::Comm("bus error"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl ReadData for Registers {
+    type Error = Error<&'static str>;
+
+    fn read_register(&mut self, register: u8) -> Result<u8, Self::Error> {
+        self.check()?;
+        Ok(self.data[usize::from(register)])
+    }
+
+    fn read_data(&mut self, payload: &mut [u8]) -> Result<(), Self::Error> {
+        self.check()?;
+        let start = usize::from(payload[0]);
+        let end = start + payload.len() - 1;
+        payload[1..].copy_from_slice(&self.data[start..end]);
+        Ok(())
+    }
+}
+
+impl WriteData for Registers {
+    type Error = Error<&'static str>;
+
+    fn write_register(&mut self, register: u8, data: u8) -> Result<(), Self::Error> {
+        self.check()?;
+        self.data[usize::from(register)] = data;
+        Ok(())
+    }
+
+    fn write_data(&mut self, payload: &mut [u8]) -> Result<(), Self::Error> {
+        self.check()?;
+        let start = usize::from(payload[0]);
+        let end = start + payload.len() - 1;
+        self.data[start..end].copy_from_slice(&payload[1..]);
+        Ok(())
+    }
+}
+
+#[test]
+fn can_set_and_read_datetime() {
+    let mut dev = Ds323x::new_ds3231_with_interface(Registers::new());
+    let datetime = NaiveDate::from_ymd_opt(2018, 8, 13)
+        .unwrap()
+        .and_hms_opt(23, 59, 58)
+        .unwrap();
+    dev.set_datetime(&datetime).unwrap();
+    assert_eq!(datetime, dev.datetime().unwrap());
+    let registers = dev.destroy_interface();
+    assert_eq!(
+        [0x58, 0x59, 0x23, 0x02, 0x13, 0x08, 0x18],
+        registers.data[..7]
+    );
+}
+
+#[test]
+fn can_access_sram_ds3232() {
+    let mut dev = Ds323x::new_ds3232_with_interface(Registers::new());
+    dev.write(0, &[1, 2, 3]).unwrap();
+    let mut data = [0; 3];
+    dev.read(0, &mut data).unwrap();
+    assert_eq!([1, 2, 3], data);
+    assert_eq!([1, 2, 3], dev.destroy_interface().data[0x14..0x17]);
+}
+
+#[test]
+fn can_set_temperature_conversion_rate_ds3232() {
+    let mut dev = Ds323x::new_ds3232_with_interface(Registers::new());
+    dev.set_temperature_conversion_rate(ds323x::TempConvRate::_128s)
+        .unwrap();
+    assert_eq!(
+        0b0001_0000,
+        dev.destroy_interface().data[0x0F] & 0b0011_0000
+    );
+}
+
+#[test]
+fn returns_interface_error() {
+    let mut registers = Registers::new();
+    registers.fail = true;
+    let mut dev = Ds323x::new_ds3231_with_interface(registers);
+    match dev.temperature() {
+        Err(Error::Comm("bus error")) => (),
+        _ => panic!("Comm error not returned."),
+    }
+}
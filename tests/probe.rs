@@ -0,0 +1,142 @@
+#![cfg(feature = "sync")]
+use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+use embedded_hal_mock::eh1::{i2c::Transaction as I2cTrans, spi::Transaction as SpiTrans};
+#[allow(unused)]
+mod common;
+use self::common::{
+    destroy_ds3231, destroy_ds3232, destroy_ds3234, new_ds3231, new_ds3232, new_ds3234,
+    BitFlags as BF, Register, DEVICE_ADDRESS as DEV_ADDR,
+};
+use ds323x::{Chip, Error};
+
+// Values of registers 0x00-0x12
+fn registers() -> Vec<u8> {
+    let mut data = vec![0, 0, 0x09, 4, 0x31, 0x03, 0x21];
+    data.extend_from_slice(&[0; 10]);
+    data.extend_from_slice(&[0x19, 0x40]);
+    data
+}
+
+fn read_registers(data: Vec<u8>) -> I2cTrans {
+    I2cTrans::write_read(DEV_ADDR, vec![Register::SECONDS], data)
+}
+
+fn read_sram(value: u8) -> I2cTrans {
+    I2cTrans::write_read(DEV_ADDR, vec![Register::SRAM_START], vec![value])
+}
+
+fn write_sram(value: u8) -> I2cTrans {
+    I2cTrans::write(DEV_ADDR, vec![Register::SRAM_START, value])
+}
+
+const NACK: ErrorKind = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+
+#[test]
+fn probe_absent_device() {
+    let mut dev = new_ds3231(&[read_registers(registers()).with_error(NACK)]);
+    assert_eq!(None, dev.probe().unwrap());
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn probe_propagates_other_errors() {
+    let mut dev = new_ds3231(&[read_registers(registers()).with_error(ErrorKind::Other)]);
+    match dev.probe() {
+        Err(Error::Comm(ErrorKind::Other)) => (),
+        _ => panic!("Comm error not returned."),
+    }
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn probe_invalid_reserved_bits() {
+    let mut dev = new_ds3231(&[read_registers(vec![0xFF; 0x13])]);
+    assert_eq!(None, dev.probe().unwrap());
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn probe_invalid_temperature_bits() {
+    let mut data = registers();
+    data[0x12] |= 0x01;
+    let mut dev = new_ds3231(&[read_registers(data)]);
+    assert_eq!(None, dev.probe().unwrap());
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn probe_ds3232() {
+    let mut dev = new_ds3231(&[
+        read_registers(registers()),
+        read_sram(0x5A),
+        write_sram(0xA5),
+        read_sram(0xA5),
+        write_sram(0x5A),
+    ]);
+    assert_eq!(Some(Chip::DS3232), dev.probe().unwrap());
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn probe_ds3232_from_status_without_writing_sram() {
+    for status in [BF::BB32KHZ, BF::CRATE1, BF::CRATE0] {
+        let mut data = registers();
+        data[usize::from(Register::STATUS)] = status;
+        let mut dev = new_ds3231(&[read_registers(data)]);
+        assert_eq!(Some(Chip::DS3232), dev.probe().unwrap());
+        destroy_ds3231(dev);
+    }
+}
+
+#[test]
+fn probe_ds3231() {
+    let mut dev = new_ds3232(&[
+        read_registers(registers()),
+        read_sram(0),
+        write_sram(0xFF),
+        read_sram(0),
+        write_sram(0),
+    ]);
+    assert_eq!(Some(Chip::DS3231), dev.probe().unwrap());
+    destroy_ds3232(dev);
+}
+
+#[test]
+fn probe_ds3231_not_acknowledging_sram() {
+    let mut dev = new_ds3231(&[read_registers(registers()), read_sram(0).with_error(NACK)]);
+    assert_eq!(Some(Chip::DS3231), dev.probe().unwrap());
+    destroy_ds3231(dev);
+}
+
+fn spi_read_registers(data: Vec<u8>) -> [SpiTrans<u8>; 3] {
+    let mut payload = vec![Register::SECONDS];
+    payload.extend(data);
+    [
+        SpiTrans::transaction_start(),
+        SpiTrans::transfer_in_place(vec![Register::SECONDS; 0x14], payload),
+        SpiTrans::transaction_end(),
+    ]
+}
+
+#[test]
+fn probe_ds3234() {
+    let mut dev = new_ds3234(&spi_read_registers(registers()));
+    assert_eq!(Some(Chip::DS3234), dev.probe().unwrap());
+    destroy_ds3234(dev);
+}
+
+#[test]
+fn probe_ds3234_with_status_bits() {
+    let mut data = registers();
+    data[usize::from(Register::STATUS)] = BF::BB32KHZ | BF::CRATE1 | BF::CRATE0;
+    let mut dev = new_ds3234(&spi_read_registers(data));
+    assert_eq!(Some(Chip::DS3234), dev.probe().unwrap());
+    destroy_ds3234(dev);
+}
+
+#[test]
+fn probe_ds3234_invalid_reserved_bits() {
+    let mut dev = new_ds3234(&spi_read_registers(vec![0; 0x13]));
+    assert_eq!(None, dev.probe().unwrap());
+    destroy_ds3234(dev);
+}
@@ -0,0 +1,124 @@
+#![cfg(feature = "sync")]
+use embedded_hal_mock::eh1::i2c::Transaction as I2cTrans;
+#[allow(unused)]
+mod common;
+use self::common::{destroy_ds3232, new_ds3232, Register, DEVICE_ADDRESS as DEV_ADDR};
+use ds323x::{Error, SramRecord};
+
+const OFFSET: u8 = 10;
+const HEADER_ADDR: u8 = Register::SRAM_START + OFFSET;
+const PAYLOAD_ADDR: u8 = HEADER_ADDR + 6;
+const PAYLOAD: [u8; 3] = [0xAA, 0xBB, 0xCC];
+// CRC-16/CCITT-FALSE of [version 1, length 3, payload]
+const HEADER_V1: [u8; 6] = [0xD3, 0x23, 1, 3, 0x80, 0xEB];
+// CRC-16/CCITT-FALSE of [version 2, length 3, payload]
+const HEADER_V2: [u8; 6] = [0xD3, 0x23, 2, 3, 0x6E, 0x39];
+
+fn read_header(header: [u8; 6]) -> I2cTrans {
+    I2cTrans::write_read(DEV_ADDR, vec![HEADER_ADDR], header.to_vec())
+}
+
+fn read_payload(payload: [u8; 3]) -> I2cTrans {
+    I2cTrans::write_read(DEV_ADDR, vec![PAYLOAD_ADDR], payload.to_vec())
+}
+
+macro_rules! load_error_test {
+    ($name:ident, $error:ident, $transactions:expr) => {
+        #[test]
+        fn $name() {
+            let trans = $transactions;
+            let mut record = SramRecord::new(new_ds3232(&trans), OFFSET, 1);
+            let mut data = [0; 16];
+            match record.load(&mut data) {
+                Err(Error::$error) => (),
+                _ => panic!(),
+            }
+            destroy_ds3232(record.destroy());
+        }
+    };
+}
+
+#[test]
+fn can_store() {
+    let mut header = vec![HEADER_ADDR];
+    header.extend_from_slice(&HEADER_V1);
+    let mut payload = vec![PAYLOAD_ADDR];
+    payload.extend_from_slice(&PAYLOAD);
+    let trans = [
+        I2cTrans::write(DEV_ADDR, payload),
+        I2cTrans::write(DEV_ADDR, header),
+    ];
+    let mut record = SramRecord::new(new_ds3232(&trans), OFFSET, 1);
+    record.store(&PAYLOAD).unwrap();
+    destroy_ds3232(record.destroy());
+}
+
+#[test]
+fn can_load() {
+    let trans = [read_header(HEADER_V1), read_payload(PAYLOAD)];
+    let mut record = SramRecord::new(new_ds3232(&trans), OFFSET, 1);
+    let mut data = [0; 16];
+    assert_eq!(3, record.load(&mut data).unwrap());
+    assert_eq!(PAYLOAD, data[..3]);
+    destroy_ds3232(record.destroy());
+}
+
+load_error_test!(
+    fresh_ram_is_not_found,
+    RecordNotFound,
+    [read_header([0xFF; 6])]
+);
+
+load_error_test!(
+    crc_mismatch_is_corrupted,
+    RecordCorrupted,
+    [read_header(HEADER_V1), read_payload([0xAA, 0xBB, 0xCD])]
+);
+
+load_error_test!(
+    length_beyond_sram_is_corrupted,
+    RecordCorrupted,
+    [read_header([0xD3, 0x23, 1, 250, 0, 0])]
+);
+
+load_error_test!(
+    other_version_is_mismatch,
+    RecordVersionMismatch,
+    [read_header(HEADER_V2), read_payload(PAYLOAD)]
+);
+
+#[test]
+fn cannot_load_into_too_small_buffer() {
+    let trans = [read_header(HEADER_V1)];
+    let mut record = SramRecord::new(new_ds3232(&trans), OFFSET, 1);
+    let mut data = [0; 2];
+    assert_invalid_input_data!(record.load(&mut data));
+    destroy_ds3232(record.destroy());
+}
+
+#[test]
+fn cannot_store_too_long_payload() {
+    let mut record = SramRecord::new(new_ds3232(&[]), OFFSET, 1);
+    assert_eq!(236 - 10 - 6, record.max_payload_len());
+    assert_invalid_input_data!(record.store(&[0; 221]));
+    destroy_ds3232(record.destroy());
+}
+
+#[test]
+fn can_invalidate() {
+    let trans = [I2cTrans::write(
+        DEV_ADDR,
+        vec![HEADER_ADDR, 0, 0, 0, 0, 0, 0],
+    )];
+    let mut record = SramRecord::new(new_ds3232(&trans), OFFSET, 1);
+    record.invalidate().unwrap();
+    destroy_ds3232(record.destroy());
+}
+
+#[test]
+fn cannot_store_without_room_for_header() {
+    let mut record = SramRecord::new(new_ds3232(&[]), 231, 1);
+    assert_eq!(0, record.max_payload_len());
+    assert_invalid_input_data!(record.store(&[]));
+    destroy_ds3232(record.destroy());
+}
@@ -0,0 +1,331 @@
+#![cfg(feature = "sync")]
+use embedded_hal_mock::eh1::i2c::Transaction as I2cTrans;
+#[allow(unused)]
+mod common;
+use self::common::{
+    destroy_ds3231, new_datetime, new_ds3231, read_status, BitFlags as BF, Register,
+    CONTROL_POR_VALUE, DEVICE_ADDRESS as DEV_ADDR, DS3231_POR_STATUS,
+};
+use ds323x::{AlarmScheduler, Error, NaiveDateTime};
+
+// 2021-03-31 (Wednesday) at the given time
+fn today_at(h: u32, min: u32, s: u32) -> NaiveDateTime {
+    new_datetime(2021, 3, 31, h, min, s)
+}
+
+// 2021-03-31 (Wednesday) at the given time
+fn read_datetime(h: u8, min: u8, s: u8) -> I2cTrans {
+    I2cTrans::write_read(
+        DEV_ADDR,
+        vec![Register::SECONDS],
+        vec![s, min, h, 4, 0x31, 0x03, 0x21],
+    )
+}
+
+fn write_alarm1(h: u8, min: u8, s: u8) -> I2cTrans {
+    I2cTrans::write(DEV_ADDR, vec![Register::ALARM1_SECONDS, s, min, h, 0x31])
+}
+
+fn enable_alarm1_interrupts() -> I2cTrans {
+    I2cTrans::write(
+        DEV_ADDR,
+        vec![Register::CONTROL, CONTROL_POR_VALUE | BF::ALARM1_INT_EN],
+    )
+}
+
+fn disable_alarm1_interrupts() -> I2cTrans {
+    I2cTrans::write(DEV_ADDR, vec![Register::CONTROL, CONTROL_POR_VALUE])
+}
+
+fn clear_alarm1_flag() -> I2cTrans {
+    I2cTrans::write(
+        DEV_ADDR,
+        vec![Register::STATUS, DS3231_POR_STATUS | BF::ALARM2F],
+    )
+}
+
+#[test]
+fn programs_earliest_alarm() {
+    let mut dev = new_ds3231(&[
+        read_datetime(0x09, 0, 0),
+        clear_alarm1_flag(),
+        write_alarm1(0x09, 0x10, 0),
+        enable_alarm1_interrupts(),
+        read_datetime(0x09, 0, 0),
+        read_datetime(0x09, 0, 1),
+        clear_alarm1_flag(),
+        write_alarm1(0x09, 0x05, 0),
+        read_datetime(0x09, 0, 1),
+    ]);
+    let mut scheduler = AlarmScheduler::<3>::new();
+    assert_eq!(
+        0,
+        dev.schedule_alarm(&mut scheduler, today_at(9, 10, 0))
+            .unwrap()
+    );
+    assert_eq!(
+        1,
+        dev.schedule_alarm(&mut scheduler, today_at(9, 5, 0))
+            .unwrap()
+    );
+    assert_eq!(Some((1, today_at(9, 5, 0))), scheduler.next());
+    assert_eq!(Some(today_at(9, 10, 0)), scheduler.get(0));
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn service_returns_fired_alarms_and_rearms() {
+    let mut dev = new_ds3231(&[
+        read_datetime(0x09, 0, 0),
+        clear_alarm1_flag(),
+        write_alarm1(0x09, 0x05, 0),
+        enable_alarm1_interrupts(),
+        read_datetime(0x09, 0, 0),
+        read_datetime(0x09, 0, 0),
+        write_alarm1(0x09, 0x05, 0),
+        read_datetime(0x09, 0, 0),
+        read_datetime(0x09, 0, 0),
+        write_alarm1(0x09, 0x05, 0),
+        read_datetime(0x09, 0, 0),
+        // service
+        read_status(BF::ALARM1F),
+        clear_alarm1_flag(),
+        read_datetime(0x09, 0x05, 0),
+        clear_alarm1_flag(),
+        write_alarm1(0x09, 0x10, 0),
+        read_datetime(0x09, 0x05, 0),
+    ]);
+    let mut scheduler = AlarmScheduler::<3>::new();
+    dev.schedule_alarm(&mut scheduler, today_at(9, 5, 0))
+        .unwrap();
+    dev.schedule_alarm(&mut scheduler, today_at(9, 10, 0))
+        .unwrap();
+    dev.schedule_alarm(&mut scheduler, today_at(9, 5, 0))
+        .unwrap();
+    let fired = dev.service_alarms(&mut scheduler).unwrap();
+    assert!(fired.contains(0));
+    assert!(!fired.contains(1));
+    assert!(fired.contains(2));
+    assert_eq!(vec![0, 2], fired.iter().collect::<Vec<_>>());
+    assert_eq!(Some((1, today_at(9, 10, 0))), scheduler.next());
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn service_without_due_alarms_rearms() {
+    let mut dev = new_ds3231(&[
+        read_datetime(0x09, 0, 0),
+        clear_alarm1_flag(),
+        write_alarm1(0x09, 0x05, 0),
+        enable_alarm1_interrupts(),
+        read_datetime(0x09, 0, 0),
+        // service
+        read_status(0),
+        read_datetime(0x09, 0x01, 0),
+        write_alarm1(0x09, 0x05, 0),
+        read_datetime(0x09, 0x01, 0),
+    ]);
+    let mut scheduler = AlarmScheduler::<2>::new();
+    dev.schedule_alarm(&mut scheduler, today_at(9, 5, 0))
+        .unwrap();
+    assert!(dev.service_alarms(&mut scheduler).unwrap().is_empty());
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn service_returns_alarms_reached_while_rearming() {
+    let mut dev = new_ds3231(&[
+        read_datetime(0x09, 0, 0),
+        clear_alarm1_flag(),
+        write_alarm1(0x09, 0x05, 0),
+        enable_alarm1_interrupts(),
+        read_datetime(0x09, 0, 0),
+        read_datetime(0x09, 0, 0),
+        write_alarm1(0x09, 0x05, 0),
+        read_datetime(0x09, 0, 0),
+        // service
+        read_status(BF::ALARM1F),
+        clear_alarm1_flag(),
+        read_datetime(0x09, 0x05, 0),
+        clear_alarm1_flag(),
+        write_alarm1(0x09, 0x05, 0x01),
+        read_datetime(0x09, 0x05, 0x01),
+        read_datetime(0x09, 0x05, 0x01),
+        disable_alarm1_interrupts(),
+    ]);
+    let mut scheduler = AlarmScheduler::<2>::new();
+    dev.schedule_alarm(&mut scheduler, today_at(9, 5, 0))
+        .unwrap();
+    dev.schedule_alarm(&mut scheduler, today_at(9, 5, 1))
+        .unwrap();
+    let fired = dev.service_alarms(&mut scheduler).unwrap();
+    assert!(fired.contains(0));
+    assert!(fired.contains(1));
+    assert_eq!(None, scheduler.next());
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn cancel_rearms_next_alarm() {
+    let mut dev = new_ds3231(&[
+        read_datetime(0x09, 0, 0),
+        clear_alarm1_flag(),
+        write_alarm1(0x09, 0x10, 0),
+        enable_alarm1_interrupts(),
+        read_datetime(0x09, 0, 0),
+        read_datetime(0x09, 0, 0),
+        clear_alarm1_flag(),
+        write_alarm1(0x09, 0x05, 0),
+        read_datetime(0x09, 0, 0),
+        // cancel
+        read_datetime(0x09, 0, 0),
+        clear_alarm1_flag(),
+        write_alarm1(0x09, 0x10, 0),
+        read_datetime(0x09, 0, 0),
+    ]);
+    let mut scheduler = AlarmScheduler::<2>::new();
+    dev.schedule_alarm(&mut scheduler, today_at(9, 10, 0))
+        .unwrap();
+    let id = dev
+        .schedule_alarm(&mut scheduler, today_at(9, 5, 0))
+        .unwrap();
+    dev.cancel_alarm(&mut scheduler, id).unwrap();
+    dev.cancel_alarm(&mut scheduler, id).unwrap();
+    assert_eq!(None, scheduler.get(id));
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn schedule_keeps_flag_of_due_alarm() {
+    let mut dev = new_ds3231(&[
+        read_datetime(0x09, 0, 0),
+        clear_alarm1_flag(),
+        write_alarm1(0x09, 0x05, 0),
+        enable_alarm1_interrupts(),
+        read_datetime(0x09, 0, 0),
+        // Alarm1 matched but was not serviced yet
+        read_datetime(0x09, 0x05, 0),
+        write_alarm1(0x09, 0x05, 0),
+        read_datetime(0x09, 0x05, 0),
+        // service
+        read_status(BF::ALARM1F),
+        clear_alarm1_flag(),
+        read_datetime(0x09, 0x05, 0),
+        clear_alarm1_flag(),
+        write_alarm1(0x09, 0x10, 0),
+        read_datetime(0x09, 0x05, 0),
+    ]);
+    let mut scheduler = AlarmScheduler::<2>::new();
+    dev.schedule_alarm(&mut scheduler, today_at(9, 5, 0))
+        .unwrap();
+    assert_eq!(
+        1,
+        dev.schedule_alarm(&mut scheduler, today_at(9, 10, 0))
+            .unwrap()
+    );
+    let fired = dev.service_alarms(&mut scheduler).unwrap();
+    assert_eq!(vec![0], fired.iter().collect::<Vec<_>>());
+    assert_eq!(Some((1, today_at(9, 10, 0))), scheduler.next());
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn cannot_schedule_in_the_past() {
+    let mut dev = new_ds3231(&[read_datetime(0x09, 0, 0)]);
+    let mut scheduler = AlarmScheduler::<1>::new();
+    assert_invalid_input_data!(dev.schedule_alarm(&mut scheduler, today_at(9, 0, 0)));
+    assert_eq!(None, scheduler.next());
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn cannot_schedule_when_full() {
+    let mut dev = new_ds3231(&[
+        read_datetime(0x09, 0, 0),
+        clear_alarm1_flag(),
+        write_alarm1(0x09, 0x05, 0),
+        enable_alarm1_interrupts(),
+        read_datetime(0x09, 0, 0),
+    ]);
+    let mut scheduler = AlarmScheduler::<1>::new();
+    dev.schedule_alarm(&mut scheduler, today_at(9, 5, 0))
+        .unwrap();
+    match dev.schedule_alarm(&mut scheduler, today_at(9, 6, 0)) {
+        Err(Error::SchedulerFull) => (),
+        _ => panic!("SchedulerFull error not returned."),
+    }
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn missed_alarm_is_not_scheduled() {
+    let mut dev = new_ds3231(&[
+        read_datetime(0x09, 0, 0),
+        clear_alarm1_flag(),
+        write_alarm1(0x09, 0, 0x01),
+        enable_alarm1_interrupts(),
+        read_datetime(0x09, 0, 0x01),
+        read_datetime(0x09, 0, 0x01),
+        disable_alarm1_interrupts(),
+    ]);
+    let mut scheduler = AlarmScheduler::<1>::new();
+    match dev.schedule_alarm(&mut scheduler, today_at(9, 0, 1)) {
+        Err(Error::AlarmMissed) => (),
+        _ => panic!("AlarmMissed error not returned."),
+    }
+    assert_eq!(None, scheduler.next());
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn missed_alarm_rearms_next_alarm() {
+    let mut dev = new_ds3231(&[
+        read_datetime(0x09, 0, 0),
+        clear_alarm1_flag(),
+        write_alarm1(0x09, 0x10, 0),
+        enable_alarm1_interrupts(),
+        read_datetime(0x09, 0, 0),
+        read_datetime(0x09, 0, 0),
+        clear_alarm1_flag(),
+        write_alarm1(0x09, 0, 0x01),
+        read_datetime(0x09, 0, 0x01),
+        read_datetime(0x09, 0, 0x01),
+        clear_alarm1_flag(),
+        write_alarm1(0x09, 0x10, 0),
+        read_datetime(0x09, 0, 0x01),
+    ]);
+    let mut scheduler = AlarmScheduler::<2>::new();
+    dev.schedule_alarm(&mut scheduler, today_at(9, 10, 0))
+        .unwrap();
+    match dev.schedule_alarm(&mut scheduler, today_at(9, 0, 1)) {
+        Err(Error::AlarmMissed) => (),
+        _ => panic!("AlarmMissed error not returned."),
+    }
+    assert_eq!(Some((0, today_at(9, 10, 0))), scheduler.next());
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn service_disables_interrupts_after_last_alarm() {
+    let mut dev = new_ds3231(&[
+        read_datetime(0x09, 0, 0),
+        clear_alarm1_flag(),
+        write_alarm1(0x09, 0x05, 0),
+        enable_alarm1_interrupts(),
+        read_datetime(0x09, 0, 0),
+        // service
+        read_status(BF::ALARM1F),
+        clear_alarm1_flag(),
+        read_datetime(0x09, 0x05, 0),
+        disable_alarm1_interrupts(),
+        // service without any virtual alarm
+        read_status(0),
+        read_datetime(0x09, 0x05, 0x01),
+    ]);
+    let mut scheduler = AlarmScheduler::<2>::new();
+    dev.schedule_alarm(&mut scheduler, today_at(9, 5, 0))
+        .unwrap();
+    assert!(dev.service_alarms(&mut scheduler).unwrap().contains(0));
+    assert!(dev.service_alarms(&mut scheduler).unwrap().is_empty());
+    destroy_ds3231(dev);
+}
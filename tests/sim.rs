@@ -0,0 +1,511 @@
+#![cfg(feature = "sim")]
+use core::time::Duration;
+#[allow(unused)]
+mod common;
+use self::common::new_datetime;
+use ds323x::{
+    sim::Simulator, Alarm2Matching, AlarmScheduler, Chip, DateTimeAccess, DayAlarm2, Ds323x, Hours,
+    NaiveTime, Rtcc, Schedule, SqWFreq, Sram,
+};
+use embedded_hal::digital::InputPin;
+
+#[test]
+fn starts_with_oscillator_stop_flag_set() {
+    let sim = Simulator::new_ds3231();
+    let mut dev = Ds323x::new_ds3231(sim.i2c());
+    assert!(dev.has_been_stopped().unwrap());
+    dev.clear_has_been_stopped_flag().unwrap();
+    assert!(!dev.has_been_stopped().unwrap());
+    assert_eq!(new_datetime(2000, 1, 1, 0, 0, 0), dev.datetime().unwrap());
+}
+
+macro_rules! rollover_test {
+    ($name:ident, $from:expr, $to:expr) => {
+        #[test]
+        fn $name() {
+            let sim = Simulator::new_ds3231();
+            let mut dev = Ds323x::new_ds3231(sim.i2c());
+            dev.set_datetime(&$from).unwrap();
+            sim.advance(Duration::from_secs(1));
+            assert_eq!($to, dev.datetime().unwrap());
+        }
+    };
+}
+
+rollover_test!(
+    rolls_over_minute,
+    new_datetime(2021, 3, 31, 10, 20, 59),
+    new_datetime(2021, 3, 31, 10, 21, 0)
+);
+rollover_test!(
+    rolls_over_month,
+    new_datetime(2021, 4, 30, 23, 59, 59),
+    new_datetime(2021, 5, 1, 0, 0, 0)
+);
+rollover_test!(
+    rolls_over_to_leap_day,
+    new_datetime(2024, 2, 28, 23, 59, 59),
+    new_datetime(2024, 2, 29, 0, 0, 0)
+);
+rollover_test!(
+    rolls_over_february,
+    new_datetime(2023, 2, 28, 23, 59, 59),
+    new_datetime(2023, 3, 1, 0, 0, 0)
+);
+rollover_test!(
+    rolls_over_century,
+    new_datetime(2099, 12, 31, 23, 59, 59),
+    new_datetime(2100, 1, 1, 0, 0, 0)
+);
+
+#[test]
+fn advances_weekday() {
+    let sim = Simulator::new_ds3231();
+    let mut dev = Ds323x::new_ds3231(sim.i2c());
+    dev.set_weekday(7).unwrap();
+    sim.advance(Duration::from_secs(24 * 60 * 60));
+    assert_eq!(1, dev.weekday().unwrap());
+}
+
+#[test]
+fn rolls_over_12h_format() {
+    let sim = Simulator::new_ds3232();
+    let mut dev = Ds323x::new_ds3232(sim.i2c());
+    dev.set_hours(Hours::AM(11)).unwrap();
+    dev.set_minutes(59).unwrap();
+    dev.set_seconds(59).unwrap();
+    sim.advance(Duration::from_secs(1));
+    assert_eq!(Hours::PM(12), dev.hours().unwrap());
+    sim.advance(Duration::from_secs(12 * 60 * 60));
+    assert_eq!(Hours::AM(12), dev.hours().unwrap());
+}
+
+#[test]
+fn accumulates_fractions_of_a_second() {
+    let sim = Simulator::new_ds3231();
+    let mut dev = Ds323x::new_ds3231(sim.i2c());
+    sim.advance(Duration::from_millis(600));
+    assert_eq!(0, dev.seconds().unwrap());
+    sim.advance(Duration::from_millis(600));
+    assert_eq!(1, dev.seconds().unwrap());
+    assert_eq!(Duration::from_millis(200), sim.subsecond());
+}
+
+#[test]
+fn writing_seconds_resets_subsecond() {
+    let sim = Simulator::new_ds3231();
+    let mut dev = Ds323x::new_ds3231(sim.i2c());
+    sim.advance(Duration::from_millis(600));
+    dev.set_seconds(10).unwrap();
+    assert_eq!(Duration::ZERO, sim.subsecond());
+}
+
+#[test]
+fn sets_alarm1_flag_on_match() {
+    let sim = Simulator::new_ds3231();
+    let mut dev = Ds323x::new_ds3231(sim.i2c());
+    dev.set_datetime(&new_datetime(2021, 3, 31, 10, 20, 0))
+        .unwrap();
+    dev.set_alarm1_hms(NaiveTime::from_hms_opt(10, 20, 30).unwrap())
+        .unwrap();
+    sim.advance(Duration::from_secs(29));
+    assert!(!dev.has_alarm1_matched().unwrap());
+    sim.advance(Duration::from_secs(1));
+    assert!(dev.has_alarm1_matched().unwrap());
+    assert!(!dev.has_alarm2_matched().unwrap());
+    dev.clear_alarm1_matched_flag().unwrap();
+    assert!(!dev.has_alarm1_matched().unwrap());
+}
+
+#[test]
+fn sets_alarm2_flag_once_per_minute() {
+    let sim = Simulator::new_ds3231();
+    let mut dev = Ds323x::new_ds3231(sim.i2c());
+    let alarm = DayAlarm2 {
+        day: 1,
+        hour: Hours::H24(0),
+        minute: 0,
+    };
+    dev.set_alarm2_day(alarm, Alarm2Matching::OncePerMinute)
+        .unwrap();
+    dev.clear_alarm2_matched_flag().unwrap();
+    sim.advance(Duration::from_secs(59));
+    assert!(!dev.has_alarm2_matched().unwrap());
+    sim.advance(Duration::from_secs(1));
+    assert!(dev.has_alarm2_matched().unwrap());
+}
+
+#[test]
+fn stops_oscillator_on_battery_when_disabled() {
+    let sim = Simulator::new_ds3231();
+    let mut dev = Ds323x::new_ds3231(sim.i2c());
+    dev.clear_has_been_stopped_flag().unwrap();
+    dev.disable().unwrap();
+    sim.set_battery_powered(true);
+    sim.advance(Duration::from_secs(10));
+    sim.set_battery_powered(false);
+    assert!(dev.has_been_stopped().unwrap());
+    assert_eq!(0, dev.seconds().unwrap());
+}
+
+#[test]
+fn keeps_time_on_battery_when_enabled() {
+    let sim = Simulator::new_ds3231();
+    let mut dev = Ds323x::new_ds3231(sim.i2c());
+    dev.clear_has_been_stopped_flag().unwrap();
+    sim.set_battery_powered(true);
+    assert!(dev.seconds().is_err());
+    sim.advance(Duration::from_secs(10));
+    sim.set_battery_powered(false);
+    assert!(!dev.has_been_stopped().unwrap());
+    assert_eq!(10, dev.seconds().unwrap());
+}
+
+#[test]
+fn can_convert_temperature() {
+    let sim = Simulator::new_ds3231();
+    let mut dev = Ds323x::new_ds3231(sim.i2c());
+    assert_eq!(25.0, dev.temperature().unwrap());
+    sim.set_temperature(-10.3);
+    dev.convert_temperature().unwrap();
+    assert!(dev.busy().unwrap());
+    assert_eq!(25.0, dev.temperature().unwrap());
+    sim.advance(Duration::from_millis(200));
+    assert!(!dev.busy().unwrap());
+    assert_eq!(-10.25, dev.temperature().unwrap());
+}
+
+#[test]
+fn converts_temperature_automatically() {
+    let sim = Simulator::new_ds3232();
+    let mut dev = Ds323x::new_ds3232(sim.i2c());
+    sim.set_temperature(30.5);
+    sim.advance(Duration::from_secs(63));
+    assert_eq!(25.0, dev.temperature().unwrap());
+    sim.advance(Duration::from_secs(1));
+    assert_eq!(30.5, dev.temperature().unwrap());
+}
+
+#[test]
+fn can_access_sram_ds3232() {
+    let sim = Simulator::new_ds3232();
+    let mut dev = Ds323x::new_ds3232(sim.i2c());
+    dev.write(200, &[0xAB; 36]).unwrap();
+    let mut data = [0; 36];
+    dev.read(200, &mut data).unwrap();
+    assert_eq!([0xAB; 36], data);
+    assert_eq!(0xAB, sim.register(0xFF));
+}
+
+#[test]
+fn can_access_sram_ds3234() {
+    let sim = Simulator::new_ds3234();
+    let mut dev = Ds323x::new_ds3234(sim.spi());
+    let data: Vec<u8> = (0..=255).collect();
+    dev.write(0, &data).unwrap();
+    let mut read = [0; 256];
+    dev.read(0, &mut read).unwrap();
+    assert_eq!(data, read);
+    assert_eq!(0x80, sim.sram(0x80));
+}
+
+#[test]
+fn can_set_and_read_datetime_ds3234() {
+    let sim = Simulator::new_ds3234();
+    let mut dev = Ds323x::new_ds3234(sim.spi());
+    let datetime = new_datetime(2018, 8, 13, 23, 59, 58);
+    dev.set_datetime(&datetime).unwrap();
+    sim.advance(Duration::from_secs(2));
+    assert_eq!(new_datetime(2018, 8, 14, 0, 0, 0), dev.datetime().unwrap());
+}
+
+#[test]
+fn can_probe() {
+    for chip in [Chip::DS3231, Chip::DS3232] {
+        let sim = Simulator::new(chip);
+        let mut dev = Ds323x::new_ds3231(sim.i2c());
+        assert_eq!(Some(chip), dev.probe().unwrap());
+    }
+    let sim = Simulator::new_ds3234();
+    let mut dev = Ds323x::new_ds3234(sim.spi());
+    assert_eq!(Some(Chip::DS3234), dev.probe().unwrap());
+}
+
+#[test]
+fn does_not_respond_on_other_bus() {
+    let sim = Simulator::new_ds3234();
+    let mut dev = Ds323x::new_ds3231(sim.i2c());
+    assert_eq!(None, dev.probe().unwrap());
+    let sim = Simulator::new_ds3232();
+    let mut dev = Ds323x::new_ds3234(sim.spi());
+    assert_eq!(None, dev.probe().unwrap());
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn can_use_async_driver() {
+    use self::common::block_on;
+    use ds323x::{asynch, AsyncDateTimeAccess};
+    let sim = Simulator::new_ds3231();
+    let mut dev = asynch::Ds323x::new_ds3231(sim.i2c());
+    let datetime = new_datetime(2021, 3, 31, 10, 20, 59);
+    block_on(dev.set_datetime(&datetime)).unwrap();
+    sim.advance(Duration::from_secs(1));
+    let expected = new_datetime(2021, 3, 31, 10, 21, 0);
+    assert_eq!(expected, block_on(dev.datetime()).unwrap());
+}
+
+#[test]
+fn int_sqw_goes_low_on_alarm_match() {
+    let sim = Simulator::new_ds3231();
+    let mut dev = Ds323x::new_ds3231(sim.i2c());
+    let mut pin = sim.int_sqw();
+    dev.set_alarm1_hms(NaiveTime::from_hms_opt(0, 0, 30).unwrap())
+        .unwrap();
+    dev.use_int_sqw_output_as_interrupt().unwrap();
+    dev.enable_alarm1_interrupts().unwrap();
+    sim.advance(Duration::from_secs(29));
+    assert!(pin.is_high().unwrap());
+    sim.advance(Duration::from_secs(1));
+    assert!(pin.is_low().unwrap());
+    dev.clear_alarm1_matched_flag().unwrap();
+    assert!(pin.is_high().unwrap());
+}
+
+#[test]
+fn service_alarm1_schedule_rearms_until_occurrence() {
+    let sim = Simulator::new_ds3231();
+    let mut dev = Ds323x::new_ds3231(sim.i2c());
+    let schedule: Schedule = "0 12 31 may *".parse().unwrap();
+    dev.set_datetime(&new_datetime(2021, 3, 31, 11, 59, 59))
+        .unwrap();
+    assert_eq!(
+        new_datetime(2021, 5, 31, 12, 0, 0),
+        dev.set_alarm1_schedule(&schedule).unwrap()
+    );
+    assert_eq!(None, dev.service_alarm1_schedule(&schedule).unwrap());
+
+    // Matches on the 31st of March, which is not an occurrence
+    sim.advance(Duration::from_secs(1));
+    assert!(dev.has_alarm1_matched().unwrap());
+    assert_eq!(None, dev.service_alarm1_schedule(&schedule).unwrap());
+    assert!(!dev.has_alarm1_matched().unwrap());
+
+    dev.set_datetime(&new_datetime(2021, 5, 31, 11, 59, 59))
+        .unwrap();
+    sim.advance(Duration::from_secs(1));
+    assert_eq!(
+        Some(new_datetime(2021, 5, 31, 12, 0, 0)),
+        dev.service_alarm1_schedule(&schedule).unwrap()
+    );
+    assert!(!dev.has_alarm1_matched().unwrap());
+}
+
+#[test]
+fn service_alarm2_schedule_rearms_until_occurrence() {
+    let sim = Simulator::new_ds3231();
+    let mut dev = Ds323x::new_ds3231(sim.i2c());
+    let schedule: Schedule = "0 0 1 jan *".parse().unwrap();
+    dev.set_datetime(&new_datetime(2021, 3, 31, 23, 59, 59))
+        .unwrap();
+    assert_eq!(
+        new_datetime(2022, 1, 1, 0, 0, 0),
+        dev.set_alarm2_schedule(&schedule).unwrap()
+    );
+
+    // Matches on the 1st of April, which is not an occurrence
+    sim.advance(Duration::from_secs(1));
+    assert_eq!(None, dev.service_alarm2_schedule(&schedule).unwrap());
+
+    dev.set_datetime(&new_datetime(2021, 12, 31, 23, 59, 59))
+        .unwrap();
+    sim.advance(Duration::from_secs(1));
+    assert_eq!(
+        Some(new_datetime(2022, 1, 1, 0, 0, 0)),
+        dev.service_alarm2_schedule(&schedule).unwrap()
+    );
+}
+
+#[test]
+fn scheduler_clears_stale_alarm1_flag() {
+    let sim = Simulator::new_ds3231();
+    let mut dev = Ds323x::new_ds3231(sim.i2c());
+    let mut pin = sim.int_sqw();
+    dev.set_datetime(&new_datetime(2021, 3, 31, 10, 20, 0))
+        .unwrap();
+    dev.set_alarm1_hms(NaiveTime::from_hms_opt(10, 20, 1).unwrap())
+        .unwrap();
+    dev.use_int_sqw_output_as_interrupt().unwrap();
+    sim.advance(Duration::from_secs(1));
+    assert!(dev.has_alarm1_matched().unwrap());
+
+    let mut scheduler = AlarmScheduler::<2>::new();
+    let id = dev
+        .schedule_alarm(&mut scheduler, new_datetime(2021, 3, 31, 10, 30, 0))
+        .unwrap();
+    assert!(!dev.has_alarm1_matched().unwrap());
+    assert!(pin.is_high().unwrap());
+    sim.advance(Duration::from_secs(60));
+    assert!(pin.is_high().unwrap());
+    assert!(dev.service_alarms(&mut scheduler).unwrap().is_empty());
+
+    sim.advance(Duration::from_secs(9 * 60 - 1));
+    assert!(pin.is_low().unwrap());
+    assert!(dev.service_alarms(&mut scheduler).unwrap().contains(id));
+    assert!(pin.is_high().unwrap());
+}
+
+#[test]
+fn int_sqw_stays_high_when_alarm_interrupt_disabled() {
+    let sim = Simulator::new_ds3232();
+    let mut dev = Ds323x::new_ds3232(sim.i2c());
+    let mut pin = sim.int_sqw();
+    let alarm = DayAlarm2 {
+        day: 1,
+        hour: Hours::H24(0),
+        minute: 0,
+    };
+    dev.set_alarm2_day(alarm, Alarm2Matching::OncePerMinute)
+        .unwrap();
+    dev.clear_alarm2_matched_flag().unwrap();
+    dev.use_int_sqw_output_as_interrupt().unwrap();
+    sim.advance(Duration::from_secs(60));
+    assert!(dev.has_alarm2_matched().unwrap());
+    assert!(pin.is_high().unwrap());
+    dev.enable_alarm2_interrupts().unwrap();
+    assert!(pin.is_low().unwrap());
+}
+
+macro_rules! square_wave_test {
+    ($name:ident, $freq:expr, $half_period:expr) => {
+        #[test]
+        fn $name() {
+            let sim = Simulator::new_ds3231();
+            let mut dev = Ds323x::new_ds3231(sim.i2c());
+            let mut pin = sim.int_sqw();
+            dev.set_square_wave_frequency($freq).unwrap();
+            dev.use_int_sqw_output_as_square_wave().unwrap();
+            assert!(pin.is_low().unwrap());
+            sim.advance($half_period);
+            assert!(pin.is_high().unwrap());
+            sim.advance($half_period);
+            assert!(pin.is_low().unwrap());
+        }
+    };
+}
+
+square_wave_test!(
+    outputs_1hz_square_wave,
+    SqWFreq::_1Hz,
+    Duration::from_millis(500)
+);
+square_wave_test!(
+    outputs_1_024khz_square_wave,
+    SqWFreq::_1_024Hz,
+    Duration::from_micros(500)
+);
+square_wave_test!(
+    outputs_8_192khz_square_wave,
+    SqWFreq::_8_192Hz,
+    Duration::from_micros(62)
+);
+
+#[test]
+fn square_wave_stops_on_battery_unless_enabled() {
+    let sim = Simulator::new_ds3231();
+    let mut dev = Ds323x::new_ds3231(sim.i2c());
+    let mut pin = sim.int_sqw();
+    dev.set_square_wave_frequency(SqWFreq::_1Hz).unwrap();
+    dev.use_int_sqw_output_as_square_wave().unwrap();
+    sim.set_battery_powered(true);
+    assert!(pin.is_high().unwrap());
+    sim.advance(Duration::from_millis(1000));
+    assert!(pin.is_high().unwrap());
+    sim.set_battery_powered(false);
+    assert!(pin.is_low().unwrap());
+    dev.enable_square_wave().unwrap();
+    sim.set_battery_powered(true);
+    assert!(pin.is_low().unwrap());
+    sim.advance(Duration::from_millis(500));
+    assert!(pin.is_high().unwrap());
+}
+
+#[test]
+fn can_disable_32khz_output() {
+    let sim = Simulator::new_ds3231();
+    let mut dev = Ds323x::new_ds3231(sim.i2c());
+    let mut pin = sim.out_32khz();
+    assert!(pin.is_low().unwrap());
+    sim.advance(Duration::from_micros(20));
+    assert!(pin.is_high().unwrap());
+    sim.advance(Duration::from_micros(20));
+    assert!(pin.is_low().unwrap());
+    dev.disable_32khz_output().unwrap();
+    assert!(pin.is_high().unwrap());
+    sim.advance(Duration::from_micros(20));
+    assert!(pin.is_high().unwrap());
+}
+
+#[test]
+fn can_disable_32khz_output_on_battery_ds3234() {
+    let sim = Simulator::new_ds3234();
+    let mut dev = Ds323x::new_ds3234(sim.spi());
+    let mut pin = sim.out_32khz();
+    sim.set_battery_powered(true);
+    sim.advance(Duration::from_micros(20));
+    assert!(pin.is_high().unwrap());
+    sim.advance(Duration::from_micros(20));
+    assert!(pin.is_low().unwrap());
+    sim.set_battery_powered(false);
+    dev.disable_32khz_output_on_battery().unwrap();
+    sim.set_battery_powered(true);
+    assert!(pin.is_high().unwrap());
+    sim.advance(Duration::from_micros(20));
+    assert!(pin.is_high().unwrap());
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn can_wait_for_square_wave_edges() {
+    use self::common::poll_once;
+    use embedded_hal_async::digital::Wait;
+    let sim = Simulator::new_ds3231();
+    let mut dev = Ds323x::new_ds3231(sim.i2c());
+    let mut pin = sim.int_sqw();
+    dev.set_square_wave_frequency(SqWFreq::_1Hz).unwrap();
+    dev.use_int_sqw_output_as_square_wave().unwrap();
+    {
+        let mut edge = core::pin::pin!(pin.wait_for_rising_edge());
+        assert_eq!(None, poll_once(edge.as_mut()));
+        sim.advance(Duration::from_millis(400));
+        assert_eq!(None, poll_once(edge.as_mut()));
+        sim.advance(Duration::from_millis(200));
+        assert_eq!(Some(Ok(())), poll_once(edge.as_mut()));
+    }
+    // the edge is not missed when the clock jumps over the whole period
+    let mut edge = core::pin::pin!(pin.wait_for_falling_edge());
+    assert_eq!(None, poll_once(edge.as_mut()));
+    sim.advance(Duration::from_millis(1000));
+    assert_eq!(Some(Ok(())), poll_once(edge.as_mut()));
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn can_wait_for_alarm_on_int_pin() {
+    use self::common::poll_once;
+    use ds323x::{asynch, AlarmEvent};
+    let sim = Simulator::new_ds3231();
+    let mut dev = asynch::Ds323x::new_ds3231(sim.i2c());
+    let mut pin = sim.int_sqw();
+    common::block_on(dev.set_alarm1_hms(NaiveTime::from_hms_opt(0, 0, 30).unwrap())).unwrap();
+    let mut alarm = core::pin::pin!(dev.wait_for_alarm(&mut pin, AlarmEvent::Alarm1));
+    assert!(poll_once(alarm.as_mut()).is_none());
+    sim.advance(Duration::from_secs(29));
+    assert!(poll_once(alarm.as_mut()).is_none());
+    sim.advance(Duration::from_secs(1));
+    match poll_once(alarm.as_mut()) {
+        Some(Ok(AlarmEvent::Alarm1)) => (),
+        _ => panic!(),
+    }
+    assert!(sim.int_sqw().is_high().unwrap());
+}
@@ -0,0 +1,286 @@
+#![cfg(feature = "sync")]
+use embedded_hal_mock::eh1::{i2c::Transaction as I2cTrans, spi::Transaction as SpiTrans};
+#[allow(unused)]
+mod common;
+use self::common::{
+    destroy_ds3232, destroy_ds3234, new_ds3232, new_ds3234, Register, DEVICE_ADDRESS as DEV_ADDR,
+};
+use ds323x::{Error, Sram};
+
+mod ds3232 {
+    use super::*;
+
+    #[test]
+    fn can_read_sram() {
+        let trans = [I2cTrans::write_read(
+            DEV_ADDR,
+            vec![Register::SRAM_START + 3],
+            vec![0xAB, 0xCD, 0xEF],
+        )];
+        let mut dev = new_ds3232(&trans);
+        let mut data = [0; 3];
+        dev.read_sram(3, &mut data).unwrap();
+        assert_eq!([0xAB, 0xCD, 0xEF], data);
+        destroy_ds3232(dev);
+    }
+
+    #[test]
+    fn can_write_sram() {
+        let trans = [I2cTrans::write(
+            DEV_ADDR,
+            vec![Register::SRAM_START + 3, 0xAB, 0xCD, 0xEF],
+        )];
+        let mut dev = new_ds3232(&trans);
+        dev.write_sram(3, &[0xAB, 0xCD, 0xEF]).unwrap();
+        destroy_ds3232(dev);
+    }
+
+    #[test]
+    fn can_read_whole_sram_in_chunks() {
+        let expected: Vec<u8> = (0..236).map(|i| i as u8).collect();
+        let trans: Vec<I2cTrans> = expected
+            .chunks(32)
+            .enumerate()
+            .map(|(i, chunk)| {
+                I2cTrans::write_read(
+                    DEV_ADDR,
+                    vec![Register::SRAM_START + (i * 32) as u8],
+                    chunk.to_vec(),
+                )
+            })
+            .collect();
+        let mut dev = new_ds3232(&trans);
+        let mut data = [0; 236];
+        dev.read_sram(0, &mut data).unwrap();
+        assert_eq!(expected[..], data[..]);
+        destroy_ds3232(dev);
+    }
+
+    #[test]
+    fn can_write_whole_sram_in_chunks() {
+        let data: Vec<u8> = (0..236).map(|i| i as u8).collect();
+        let trans: Vec<I2cTrans> = data
+            .chunks(32)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let mut payload = vec![Register::SRAM_START + (i * 32) as u8];
+                payload.extend_from_slice(chunk);
+                I2cTrans::write(DEV_ADDR, payload)
+            })
+            .collect();
+        let mut dev = new_ds3232(&trans);
+        dev.write_sram(0, &data).unwrap();
+        destroy_ds3232(dev);
+    }
+
+    #[test]
+    fn cannot_read_past_end_of_sram() {
+        let mut dev = new_ds3232(&[]);
+        let mut data = [0; 2];
+        assert_invalid_input_data!(dev.read_sram(235, &mut data));
+        destroy_ds3232(dev);
+    }
+
+    #[test]
+    fn cannot_write_past_end_of_sram() {
+        let mut dev = new_ds3232(&[]);
+        assert_invalid_input_data!(dev.write_sram(0, &[0; 237]));
+        destroy_ds3232(dev);
+    }
+}
+
+mod ds3234 {
+    use super::*;
+
+    #[test]
+    fn can_read_sram() {
+        let trans = [
+            SpiTrans::transaction_start(),
+            SpiTrans::write_vec(vec![Register::SRAM_ADDRESS + 0x80, 200]),
+            SpiTrans::transaction_end(),
+            SpiTrans::transaction_start(),
+            SpiTrans::transfer_in_place(
+                vec![Register::SRAM_DATA, 0, 0, 0],
+                vec![Register::SRAM_DATA, 0xAB, 0xCD, 0xEF],
+            ),
+            SpiTrans::transaction_end(),
+        ];
+        let mut dev = new_ds3234(&trans);
+        let mut data = [0; 3];
+        dev.read_sram(200, &mut data).unwrap();
+        assert_eq!([0xAB, 0xCD, 0xEF], data);
+        destroy_ds3234(dev);
+    }
+
+    #[test]
+    fn can_write_sram() {
+        let trans = [
+            SpiTrans::transaction_start(),
+            SpiTrans::write_vec(vec![Register::SRAM_ADDRESS + 0x80, 200, 0xAB, 0xCD, 0xEF]),
+            SpiTrans::transaction_end(),
+        ];
+        let mut dev = new_ds3234(&trans);
+        dev.write_sram(200, &[0xAB, 0xCD, 0xEF]).unwrap();
+        destroy_ds3234(dev);
+    }
+
+    #[test]
+    fn can_read_whole_sram_in_chunks() {
+        let expected: Vec<u8> = (0..=255).collect();
+        let mut trans = vec![
+            SpiTrans::transaction_start(),
+            SpiTrans::write_vec(vec![Register::SRAM_ADDRESS + 0x80, 0]),
+            SpiTrans::transaction_end(),
+        ];
+        for chunk in expected.chunks(32) {
+            let mut write = vec![Register::SRAM_DATA];
+            write.resize(chunk.len() + 1, 0);
+            let mut read = vec![Register::SRAM_DATA];
+            read.extend_from_slice(chunk);
+            trans.push(SpiTrans::transaction_start());
+            trans.push(SpiTrans::transfer_in_place(write, read));
+            trans.push(SpiTrans::transaction_end());
+        }
+        let mut dev = new_ds3234(&trans);
+        let mut data = [0; 256];
+        dev.read_sram(0, &mut data).unwrap();
+        assert_eq!(expected[..], data[..]);
+        destroy_ds3234(dev);
+    }
+
+    #[test]
+    fn can_write_whole_sram_in_chunks() {
+        let data: Vec<u8> = (0..=255).collect();
+        let mut trans = Vec::new();
+        for (i, chunk) in data.chunks(32).enumerate() {
+            let mut payload = vec![Register::SRAM_ADDRESS + 0x80, (i * 32) as u8];
+            payload.extend_from_slice(chunk);
+            trans.push(SpiTrans::transaction_start());
+            trans.push(SpiTrans::write_vec(payload));
+            trans.push(SpiTrans::transaction_end());
+        }
+        let mut dev = new_ds3234(&trans);
+        dev.write_sram(0, &data).unwrap();
+        destroy_ds3234(dev);
+    }
+
+    #[test]
+    fn cannot_read_past_end_of_sram() {
+        let mut dev = new_ds3234(&[]);
+        let mut data = [0; 2];
+        assert_invalid_input_data!(dev.read_sram(255, &mut data));
+        destroy_ds3234(dev);
+    }
+
+    #[test]
+    fn cannot_write_past_end_of_sram() {
+        let mut dev = new_ds3234(&[]);
+        assert_invalid_input_data!(dev.write_sram(0, &[0; 257]));
+        destroy_ds3234(dev);
+    }
+}
+
+mod generic {
+    use super::*;
+
+    fn clear<S: Sram>(sram: &mut S) -> Result<(), S::Error> {
+        let capacity = sram.capacity();
+        sram.fill(0, capacity, 0)
+    }
+
+    #[test]
+    fn ds3232_capacity() {
+        let dev = new_ds3232(&[]);
+        assert_eq!(236, dev.capacity());
+        destroy_ds3232(dev);
+    }
+
+    #[test]
+    fn ds3234_capacity() {
+        let dev = new_ds3234(&[]);
+        assert_eq!(256, dev.capacity());
+        destroy_ds3234(dev);
+    }
+
+    #[test]
+    fn can_read_and_write_ds3232() {
+        let trans = [
+            I2cTrans::write(DEV_ADDR, vec![Register::SRAM_START + 1, 0xAB]),
+            I2cTrans::write_read(DEV_ADDR, vec![Register::SRAM_START + 1], vec![0xAB]),
+        ];
+        let mut dev = new_ds3232(&trans);
+        Sram::write(&mut dev, 1, &[0xAB]).unwrap();
+        let mut data = [0];
+        Sram::read(&mut dev, 1, &mut data).unwrap();
+        assert_eq!([0xAB], data);
+        destroy_ds3232(dev);
+    }
+
+    #[test]
+    fn can_fill_ds3232() {
+        let trans = [I2cTrans::write(
+            DEV_ADDR,
+            vec![Register::SRAM_START + 100, 0x5A, 0x5A, 0x5A],
+        )];
+        let mut dev = new_ds3232(&trans);
+        dev.fill(100, 3, 0x5A).unwrap();
+        destroy_ds3232(dev);
+    }
+
+    #[test]
+    fn can_fill_ds3234() {
+        let trans = [
+            SpiTrans::transaction_start(),
+            SpiTrans::write_vec(vec![Register::SRAM_ADDRESS + 0x80, 100, 0x5A, 0x5A, 0x5A]),
+            SpiTrans::transaction_end(),
+        ];
+        let mut dev = new_ds3234(&trans);
+        dev.fill(100, 3, 0x5A).unwrap();
+        destroy_ds3234(dev);
+    }
+
+    #[test]
+    fn can_clear_whole_ds3232() {
+        let trans: Vec<I2cTrans> = (0..236)
+            .step_by(32)
+            .map(|start| {
+                let len = (236 - start).min(32);
+                let mut payload = vec![Register::SRAM_START + start as u8];
+                payload.resize(len + 1, 0);
+                I2cTrans::write(DEV_ADDR, payload)
+            })
+            .collect();
+        let mut dev = new_ds3232(&trans);
+        clear(&mut dev).unwrap();
+        destroy_ds3232(dev);
+    }
+
+    #[test]
+    fn can_clear_whole_ds3234() {
+        let mut trans = Vec::new();
+        for start in (0..256).step_by(32) {
+            let mut payload = vec![Register::SRAM_ADDRESS + 0x80, start as u8];
+            payload.resize(34, 0);
+            trans.push(SpiTrans::transaction_start());
+            trans.push(SpiTrans::write_vec(payload));
+            trans.push(SpiTrans::transaction_end());
+        }
+        let mut dev = new_ds3234(&trans);
+        clear(&mut dev).unwrap();
+        destroy_ds3234(dev);
+    }
+
+    #[test]
+    fn cannot_fill_past_end_of_ds3232() {
+        let mut dev = new_ds3232(&[]);
+        assert_invalid_input_data!(dev.fill(200, 37, 0));
+        destroy_ds3232(dev);
+    }
+
+    #[test]
+    fn cannot_fill_past_end_of_ds3234() {
+        let mut dev = new_ds3234(&[]);
+        assert_invalid_input_data!(dev.fill(1, 256, 0));
+        destroy_ds3234(dev);
+    }
+}
@@ -1,9 +1,11 @@
+#![cfg(feature = "sync")]
 use embedded_hal_mock::eh1::{i2c::Transaction as I2cTrans, spi::Transaction as SpiTrans};
 mod common;
 use self::common::{
     destroy_ds3231, destroy_ds3232, destroy_ds3234, new_ds3231, new_ds3232, new_ds3234,
-    BitFlags as BF, Register, DEVICE_ADDRESS as DEV_ADDR,
+    BitFlags as BF, Register, DEVICE_ADDRESS as DEV_ADDR, DS3231_POR_STATUS, DS323X_POR_STATUS,
 };
+use ds323x::AlarmFlags;
 
 get_param_test!(running, running, CONTROL, true, 0);
 get_param_test!(is_not_running, running, CONTROL, false, BF::EOSC);
@@ -49,3 +51,142 @@ get_param_read_array_test!(
     [0b0111_1111, 0b1100_0000],
     [0, 0]
 );
+
+macro_rules! take_alarm_flags_test {
+    ($name:ident, $status:expr, $alarm1:expr, $alarm2:expr, $written_flags:expr) => {
+        mod $name {
+            use super::*;
+            const EXPECTED: AlarmFlags = AlarmFlags {
+                alarm1: $alarm1,
+                alarm2: $alarm2,
+            };
+            get_test!(
+                can_get_ds3231,
+                take_alarm_flags,
+                new_ds3231,
+                destroy_ds3231,
+                EXPECTED,
+                [
+                    I2cTrans::write_read(DEV_ADDR, vec![Register::STATUS], vec![$status]),
+                    I2cTrans::write(
+                        DEV_ADDR,
+                        vec![Register::STATUS, DS3231_POR_STATUS | $written_flags]
+                    )
+                ]
+            );
+            get_test!(
+                can_get_ds3232,
+                take_alarm_flags,
+                new_ds3232,
+                destroy_ds3232,
+                EXPECTED,
+                [
+                    I2cTrans::write_read(DEV_ADDR, vec![Register::STATUS], vec![$status]),
+                    I2cTrans::write(
+                        DEV_ADDR,
+                        vec![Register::STATUS, DS323X_POR_STATUS | $written_flags]
+                    )
+                ]
+            );
+            get_test!(
+                can_get_ds3234,
+                take_alarm_flags,
+                new_ds3234,
+                destroy_ds3234,
+                EXPECTED,
+                [
+                    SpiTrans::transaction_start(),
+                    SpiTrans::transfer_in_place(
+                        vec![Register::STATUS, 0],
+                        vec![Register::STATUS, $status]
+                    ),
+                    SpiTrans::transaction_end(),
+                    SpiTrans::transaction_start(),
+                    SpiTrans::write_vec(vec![
+                        Register::STATUS + 0x80,
+                        DS323X_POR_STATUS | $written_flags
+                    ]),
+                    SpiTrans::transaction_end(),
+                ]
+            );
+        }
+    };
+}
+
+take_alarm_flags_test!(take_alarm1_flag, BF::ALARM1F, true, false, BF::ALARM2F);
+take_alarm_flags_test!(take_alarm2_flag, BF::ALARM2F, false, true, BF::ALARM1F);
+take_alarm_flags_test!(take_both_alarm_flags, 0xFF, true, true, 0);
+
+get_param_test!(
+    take_no_alarm_flags,
+    take_alarm_flags,
+    STATUS,
+    AlarmFlags::default(),
+    !(BF::ALARM1F | BF::ALARM2F)
+);
+
+mod status {
+    use super::*;
+    use ds323x::{Status, TempConvRate};
+
+    get_param_test!(
+        all_set,
+        status,
+        STATUS,
+        Status {
+            has_been_stopped: true,
+            enabled_32khz_output_on_battery: true,
+            temperature_conversion_rate: TempConvRate::_512s,
+            enabled_32khz_output: true,
+            busy: true,
+            alarm2_matched: true,
+            alarm1_matched: true,
+        },
+        0xFF
+    );
+    get_param_test!(
+        none_set,
+        status,
+        STATUS,
+        Status {
+            has_been_stopped: false,
+            enabled_32khz_output_on_battery: false,
+            temperature_conversion_rate: TempConvRate::_64s,
+            enabled_32khz_output: false,
+            busy: false,
+            alarm2_matched: false,
+            alarm1_matched: false,
+        },
+        0
+    );
+    get_param_test!(
+        some_set,
+        status,
+        STATUS,
+        Status {
+            has_been_stopped: true,
+            enabled_32khz_output_on_battery: false,
+            temperature_conversion_rate: TempConvRate::_128s,
+            enabled_32khz_output: true,
+            busy: false,
+            alarm2_matched: true,
+            alarm1_matched: false,
+        },
+        BF::OSC_STOP | BF::CRATE0 | BF::EN32KHZ | BF::ALARM2F
+    );
+    get_param_test!(
+        conversion_rate_256s,
+        status,
+        STATUS,
+        Status {
+            has_been_stopped: false,
+            enabled_32khz_output_on_battery: false,
+            temperature_conversion_rate: TempConvRate::_256s,
+            enabled_32khz_output: false,
+            busy: false,
+            alarm2_matched: false,
+            alarm1_matched: false,
+        },
+        BF::CRATE1
+    );
+}
@@ -0,0 +1,95 @@
+#![cfg(all(feature = "sync", feature = "storage"))]
+use embedded_hal_mock::eh1::{i2c::Transaction as I2cTrans, spi::Transaction as SpiTrans};
+#[allow(unused)]
+mod common;
+use self::common::{
+    destroy_ds3232, destroy_ds3234, new_ds3232, new_ds3234, Register, DEVICE_ADDRESS as DEV_ADDR,
+};
+use ds323x::{Error, SramStorage};
+use embedded_storage::{
+    nor_flash::{NorFlash, NorFlashError, NorFlashErrorKind, ReadNorFlash},
+    ReadStorage, Storage,
+};
+
+#[test]
+fn can_get_capacity() {
+    let storage = SramStorage::new(new_ds3232(&[]));
+    assert_eq!(236, ReadStorage::capacity(&storage));
+    destroy_ds3232(storage.destroy());
+
+    let storage = SramStorage::new(new_ds3234(&[]));
+    assert_eq!(256, ReadNorFlash::capacity(&storage));
+    destroy_ds3234(storage.destroy());
+}
+
+#[test]
+fn can_read() {
+    let trans = [I2cTrans::write_read(
+        DEV_ADDR,
+        vec![Register::SRAM_START + 10],
+        vec![1, 2],
+    )];
+    let mut storage = SramStorage::new(new_ds3232(&trans));
+    let mut data = [0; 2];
+    ReadStorage::read(&mut storage, 10, &mut data).unwrap();
+    assert_eq!([1, 2], data);
+    destroy_ds3232(storage.destroy());
+}
+
+#[test]
+fn can_write() {
+    let trans = [I2cTrans::write(
+        DEV_ADDR,
+        vec![Register::SRAM_START + 10, 1, 2],
+    )];
+    let mut storage = SramStorage::new(new_ds3232(&trans));
+    Storage::write(&mut storage, 10, &[1, 2]).unwrap();
+    destroy_ds3232(storage.destroy());
+}
+
+#[test]
+fn can_write_nor_flash() {
+    let trans = [
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec(vec![Register::SRAM_ADDRESS + 0x80, 255, 7]),
+        SpiTrans::transaction_end(),
+    ];
+    let mut storage = SramStorage::new(new_ds3234(&trans));
+    NorFlash::write(&mut storage, 255, &[7]).unwrap();
+    destroy_ds3234(storage.destroy());
+}
+
+#[test]
+fn erase_sets_bytes_to_ff() {
+    let trans = [
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec(vec![Register::SRAM_ADDRESS + 0x80, 4, 0xFF, 0xFF, 0xFF]),
+        SpiTrans::transaction_end(),
+    ];
+    let mut storage = SramStorage::new(new_ds3234(&trans));
+    storage.erase(4, 7).unwrap();
+    destroy_ds3234(storage.destroy());
+}
+
+#[test]
+fn empty_access_at_end_is_valid() {
+    let mut storage = SramStorage::new(new_ds3234(&[]));
+    ReadStorage::read(&mut storage, 256, &mut []).unwrap();
+    Storage::write(&mut storage, 256, &[]).unwrap();
+    storage.erase(256, 256).unwrap();
+    destroy_ds3234(storage.destroy());
+}
+
+#[test]
+fn out_of_bounds_access_is_reported() {
+    let mut storage = SramStorage::new(new_ds3232(&[]));
+    let mut data = [0; 2];
+    match ReadStorage::read(&mut storage, 235, &mut data) {
+        Err(e) => assert_eq!(NorFlashErrorKind::OutOfBounds, e.kind()),
+        Ok(_) => panic!(),
+    }
+    assert_invalid_input_data!(Storage::write(&mut storage, 1000, &[0]));
+    assert_invalid_input_data!(storage.erase(5, 4));
+    assert_invalid_input_data!(storage.erase(0, 237));
+    destroy_ds3232(storage.destroy());
+}
@@ -0,0 +1,195 @@
+#![cfg(feature = "sync")]
+use core::time::Duration;
+use embedded_hal_mock::eh1::{
+    delay::{CheckedDelay, Transaction as DelayTrans},
+    i2c::Transaction as I2cTrans,
+    spi::Transaction as SpiTrans,
+};
+#[allow(unused)]
+mod common;
+use self::common::{
+    destroy_ds3231, destroy_ds3234, new_ds3231, new_ds3234, read_status, BitFlags as BF, Register,
+    DEVICE_ADDRESS as DEV_ADDR, DS3231_POR_STATUS, DS323X_POR_STATUS,
+};
+use ds323x::{AlarmEvent, Error};
+
+const POLL: Duration = Duration::from_millis(10);
+const TIMEOUT: Duration = Duration::from_millis(25);
+
+fn write_status(status: u8) -> I2cTrans {
+    I2cTrans::write(DEV_ADDR, vec![Register::STATUS, status])
+}
+
+macro_rules! wait_test {
+    ($name:ident, $status:expr, $written_status:expr, $expected:expr) => {
+        #[test]
+        fn $name() {
+            let mut dev = new_ds3231(&[
+                read_status(0),
+                read_status($status),
+                write_status(DS3231_POR_STATUS | $written_status),
+            ]);
+            let mut delay = CheckedDelay::new(&[DelayTrans::delay_us(10_000)]);
+            assert_eq!(
+                $expected,
+                dev.wait_for_alarm(&mut delay, POLL, TIMEOUT).unwrap()
+            );
+            delay.done();
+            destroy_ds3231(dev);
+        }
+    };
+}
+
+wait_test!(alarm1, BF::ALARM1F, BF::ALARM2F, AlarmEvent::Alarm1);
+wait_test!(alarm2, BF::ALARM2F, BF::ALARM1F, AlarmEvent::Alarm2);
+wait_test!(both, BF::ALARM1F | BF::ALARM2F, 0, AlarmEvent::Both);
+
+#[test]
+fn alarm_already_matched_ds3234() {
+    let mut dev = new_ds3234(&[
+        SpiTrans::transaction_start(),
+        SpiTrans::transfer_in_place(
+            vec![Register::STATUS, 0],
+            vec![Register::STATUS, BF::ALARM1F],
+        ),
+        SpiTrans::transaction_end(),
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec(vec![
+            Register::STATUS + 0x80,
+            DS323X_POR_STATUS | BF::ALARM2F,
+        ]),
+        SpiTrans::transaction_end(),
+    ]);
+    let mut delay = CheckedDelay::new(&[]);
+    assert_eq!(
+        AlarmEvent::Alarm1,
+        dev.wait_for_alarm(&mut delay, POLL, TIMEOUT).unwrap()
+    );
+    delay.done();
+    destroy_ds3234(dev);
+}
+
+#[test]
+fn times_out() {
+    let mut dev = new_ds3231(&[
+        read_status(0),
+        read_status(0),
+        read_status(0),
+        read_status(0),
+    ]);
+    let mut delay = CheckedDelay::new(&[
+        DelayTrans::delay_us(10_000),
+        DelayTrans::delay_us(10_000),
+        DelayTrans::delay_us(10_000),
+    ]);
+    match dev.wait_for_alarm(&mut delay, POLL, TIMEOUT) {
+        Err(Error::Timeout) => (),
+        _ => panic!("Timeout error not returned."),
+    }
+    delay.done();
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn raises_zero_poll_interval() {
+    let mut dev = new_ds3231(&[read_status(0), read_status(0), read_status(0)]);
+    let mut delay = CheckedDelay::new(&[DelayTrans::delay_us(1), DelayTrans::delay_us(1)]);
+    match dev.wait_for_alarm(&mut delay, Duration::ZERO, Duration::from_micros(2)) {
+        Err(Error::Timeout) => (),
+        _ => panic!("Timeout error not returned."),
+    }
+    delay.done();
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn splits_long_poll_interval() {
+    let interval = Duration::from_micros(u64::from(u32::MAX) + 1);
+    let mut dev = new_ds3231(&[read_status(0), read_status(0)]);
+    let mut delay = CheckedDelay::new(&[DelayTrans::delay_us(u32::MAX), DelayTrans::delay_us(1)]);
+    match dev.wait_for_alarm(&mut delay, interval, interval) {
+        Err(Error::Timeout) => (),
+        _ => panic!("Timeout error not returned."),
+    }
+    delay.done();
+    destroy_ds3231(dev);
+}
+
+#[cfg(feature = "async")]
+mod asynch {
+    use self::common::{block_on, CONTROL_POR_VALUE};
+    use super::*;
+    use ds323x::{asynch::Ds323x, ic};
+    use embedded_hal::digital::ErrorKind;
+    use embedded_hal_mock::eh1::{
+        digital::{Mock as PinMock, State, Transaction as PinTrans},
+        i2c::Mock as I2cMock,
+        MockError,
+    };
+
+    fn new_ds3231(
+        transactions: &[I2cTrans],
+    ) -> Ds323x<ds323x::asynch::I2cInterface<I2cMock>, ic::DS3231> {
+        Ds323x::new_ds3231(I2cMock::new(transactions))
+    }
+
+    fn write_control(control: u8) -> I2cTrans {
+        I2cTrans::write(DEV_ADDR, vec![Register::CONTROL, control])
+    }
+
+    fn enable_only_alarm1_interrupts() -> [I2cTrans; 3] {
+        [
+            write_control(CONTROL_POR_VALUE),
+            write_control(CONTROL_POR_VALUE),
+            write_control(CONTROL_POR_VALUE | BF::ALARM1_INT_EN),
+        ]
+    }
+
+    #[test]
+    fn waits_for_pin_and_clears_flag() {
+        let mut trans = enable_only_alarm1_interrupts().to_vec();
+        trans.push(read_status(BF::ALARM1F | BF::ALARM2F));
+        trans.push(write_status(DS3231_POR_STATUS | BF::ALARM2F));
+        let mut dev = new_ds3231(&trans);
+        let mut pin = PinMock::new(&[PinTrans::wait_for_state(State::Low)]);
+        assert_eq!(
+            AlarmEvent::Alarm1,
+            block_on(dev.wait_for_alarm(&mut pin, AlarmEvent::Alarm1)).unwrap()
+        );
+        pin.done();
+        dev.destroy_ds3231().done();
+    }
+
+    #[test]
+    fn disables_interrupt_of_other_alarm() {
+        let mut dev = new_ds3231(&[
+            write_control(CONTROL_POR_VALUE | BF::ALARM2_INT_EN),
+            write_control(CONTROL_POR_VALUE | BF::ALARM2_INT_EN),
+            write_control(CONTROL_POR_VALUE),
+            write_control(CONTROL_POR_VALUE | BF::ALARM1_INT_EN),
+            read_status(BF::ALARM1F | BF::ALARM2F),
+            write_status(DS3231_POR_STATUS | BF::ALARM2F),
+        ]);
+        let mut pin = PinMock::new(&[PinTrans::wait_for_state(State::Low)]);
+        block_on(dev.enable_alarm2_interrupts()).unwrap();
+        assert_eq!(
+            AlarmEvent::Alarm1,
+            block_on(dev.wait_for_alarm(&mut pin, AlarmEvent::Alarm1)).unwrap()
+        );
+        pin.done();
+        dev.destroy_ds3231().done();
+    }
+
+    #[test]
+    fn returns_pin_error_kind() {
+        let mut dev = new_ds3231(&enable_only_alarm1_interrupts());
+        let mut pin = PinMock::new(&[PinTrans::wait_for_state(State::Low)
+            .with_error(MockError::Io(std::io::ErrorKind::NotConnected))]);
+        match block_on(dev.wait_for_alarm(&mut pin, AlarmEvent::Alarm1)) {
+            Err(Error::Pin(ErrorKind::Other)) => (),
+            _ => panic!("Pin error not returned."),
+        }
+        pin.done();
+        dev.destroy_ds3231().done();
+    }
+}